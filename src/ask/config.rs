@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 
-use crate::client::ProviderAPI;
+use crate::ask::request::AskRequest;
+use crate::client::{compat::CompatProfile, openai::OpenAiBackend, ratelimit::RateLimit, retry::RetryPolicy, ProviderAPI};
 
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -11,13 +13,47 @@ pub struct AskConfig {
     pub url: String,
     pub api_key: String,
     pub request_timeout: Duration,
+    #[serde(default)]
+    pub app_url: Option<String>,  // attribution: sent as `HTTP-Referer` (OpenRouter)
+    #[serde(default)]
+    pub app_name: Option<String>, // attribution: sent as `X-Title` (OpenRouter)
+    #[serde(default)]
+    pub azure: Option<AzureOptions>, // OpenAI only: `url` is then the Azure resource endpoint
+    #[serde(default)]
+    pub openai_backend: OpenAiBackend, // OpenAI only: Chat Completions unless set to the Responses API
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>, // retry transient failures; none when unset
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>, // client-side throttling shared per (api, model, api_key); none when unset
+    #[serde(skip)]
+    pub http_client: Option<reqwest::Client>, // shared connection pool; a fresh client is built per call when unset
+}
+
+/// Azure OpenAI deployment settings (used with `ProviderAPI::OpenAI`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AzureOptions {
+    pub api_version: String, // e.g. "2024-10-21"
+    #[serde(default)]
+    pub deployments: HashMap<String, String>, // model name -> deployment id
+}
+
+impl AzureOptions {
+    /// Deployment serving `model`; unmapped models are assumed to be deployed under their own name.
+    pub fn deployment_for(&self, model: &str) -> String {
+        self.deployments.get(model).cloned().unwrap_or_else(|| model.to_string())
+    }
 }
 
 impl AskConfig {
     pub fn new(model: String, api: ProviderAPI, api_key: String, url: Option<String>, request_timeout: Option<Duration>) -> Self {
-        let url = match api {
+        let url = match &api {
             ProviderAPI::OpenAI => url.unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
             ProviderAPI::Anthropic => url.unwrap_or_else(|| "https://api.anthropic.com/v1".to_string()),
+            ProviderAPI::OpenRouter => url.unwrap_or_else(|| "https://openrouter.ai/api/v1".to_string()),
+            ProviderAPI::Gemini => url.unwrap_or_else(|| "https://generativelanguage.googleapis.com/v1beta".to_string()),
+            ProviderAPI::Ollama => url.unwrap_or_else(|| "http://localhost:11434".to_string()),
+            ProviderAPI::OpenAiCompatible { profile } => url.unwrap_or_else(|| profile.default_url().to_string()),
+            ProviderAPI::Custom(_) => url.unwrap_or_default(),
         };
         let request_timeout = request_timeout.unwrap_or_else(|| Duration::from_secs(30));
         Self {
@@ -26,14 +62,85 @@ impl AskConfig {
             url,
             api_key,
             request_timeout,
+            app_url: None,
+            app_name: None,
+            azure: None,
+            openai_backend: OpenAiBackend::default(),
+            retry: None,
+            rate_limit: None,
+            http_client: None,
         }
     }
 
+    /// Azure OpenAI: `endpoint` is the resource url, e.g. `https://my-resource.openai.azure.com`.
+    pub fn azure(model: String, endpoint: String, api_key: String, api_version: String, deployments: HashMap<String, String>) -> Self {
+        let mut config = Self::new(model, ProviderAPI::OpenAI, api_key, Some(endpoint), None);
+        config.azure = Some(AzureOptions { api_version, deployments });
+        config
+    }
+
+    /// Attribution headers OpenRouter uses for app rankings, sent with each OpenRouter request.
+    pub fn with_attribution(mut self, app_url: Option<String>, app_name: Option<String>) -> Self {
+        self.app_url = app_url;
+        self.app_name = app_name;
+        self
+    }
+
+    /// Send OpenAI chat requests to `backend` (e.g. the Responses API) instead of Chat Completions.
+    pub fn with_openai_backend(mut self, backend: OpenAiBackend) -> Self {
+        self.openai_backend = backend;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Throttle calls before they are sent. Every config with the same api, model and key shares one budget.
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Reuse `http_client` (and its connection pool) instead of building a client per call.
+    /// Per-call settings such as attribution headers are still added to each request.
+    pub fn with_http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// The config a call with `request` runs on: `request.model`, when set, replaces `model`.
+    pub fn for_request(mut self, request: &AskRequest) -> Self {
+        if !request.model.is_empty() {
+            self.model = request.model.clone();
+        }
+        self
+    }
+
     pub fn default_openai(api_key: String) -> Self {
         Self::new("gpt-5-nano".to_string(), ProviderAPI::OpenAI, api_key, None, None)
     }
 
     pub fn default_anthropic(api_key: String) -> Self {
-        Self::new("sonnet".to_string(), ProviderAPI::Anthropic, api_key, None, None)
+        Self::new("claude-sonnet-4-5".to_string(), ProviderAPI::Anthropic, api_key, None, None)
+    }
+
+    pub fn default_openrouter(api_key: String) -> Self {
+        Self::new("openrouter/auto".to_string(), ProviderAPI::OpenRouter, api_key, None, None)
+    }
+
+    pub fn default_gemini(api_key: String) -> Self {
+        Self::new("gemini-2.5-flash".to_string(), ProviderAPI::Gemini, api_key, None, None)
+    }
+
+    /// Any OpenAI-compatible backend at its profile's default url (override `url` for self-hosted servers).
+    pub fn default_compatible(profile: CompatProfile, model: String, api_key: String) -> Self {
+        Self::new(model, ProviderAPI::OpenAiCompatible { profile }, api_key, None, None)
+    }
+
+    /// Local Ollama on the default port; local generation is slow, so the timeout is generous.
+    pub fn default_ollama(model: String) -> Self {
+        Self::new(model, ProviderAPI::Ollama, String::new(), None, Some(Duration::from_secs(300)))
     }
 }
@@ -2,3 +2,5 @@ pub mod msg;
 pub mod request;
 pub mod response;
 pub mod config;
+pub mod schema;
+pub mod tool;
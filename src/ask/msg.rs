@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 
-/// A simple chat role set. Expand later if you add multi-part content.
+use crate::ask::tool::ToolCall;
+
+/// A simple chat role set.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Role {
@@ -15,7 +17,184 @@ pub enum Role {
 #[serde(rename_all = "camelCase")]
 pub struct Msg {
     pub role: Role,
-    pub content: String,
+    pub content: MsgContent,
     #[serde(default)]
     pub name: Option<String>, // optional sender label
-}
\ No newline at end of file
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>, // assistant turns: tools the model asked to run
+    #[serde(default)]
+    pub tool_call_id: Option<String>, // tool turns: which call this result answers (falls back to `name`)
+    #[serde(default)]
+    pub cache: Option<CacheControl>, // prompt cache breakpoint after this message (Anthropic)
+    #[serde(default)]
+    pub thinking: Vec<ThinkingBlock>, // assistant turns: `AskResponse.thinking`, passed back unchanged for tool use
+}
+
+impl Msg {
+    pub fn new(role: Role, content: impl Into<MsgContent>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+            name: None,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+            cache: None,
+            thinking: Vec::new(),
+        }
+    }
+
+    pub fn user(content: impl Into<MsgContent>) -> Self {
+        Self::new(Role::User, content)
+    }
+
+    pub fn assistant(content: impl Into<MsgContent>) -> Self {
+        Self::new(Role::Assistant, content)
+    }
+
+    /// Id of the tool call a `Role::Tool` message answers.
+    pub fn tool_call_id(&self) -> String {
+        self.tool_call_id.clone().or_else(|| self.name.clone()).unwrap_or_default()
+    }
+}
+
+/// Reasoning a model produced before answering. Signatures and redacted payloads must be sent
+/// back unchanged when the turn is replayed (Anthropic requires this while tool calls are open).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ThinkingBlock {
+    Thinking {
+        text: String,
+        #[serde(default)]
+        signature: Option<String>,
+    },
+    Redacted { data: String }, // encrypted by the provider
+}
+
+/// Marks the end of a cacheable prompt prefix. Providers with automatic caching ignore it.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheControl {
+    #[serde(default)]
+    pub ttl: Option<CacheTtl>, // provider default (5 minutes) when unset
+}
+
+impl CacheControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_ttl(ttl: CacheTtl) -> Self {
+        Self { ttl: Some(ttl) }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CacheTtl {
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour, // writes cost more than 5-minute ones
+}
+
+/// Message body: plain text (serialized as a bare string, as before) or multimodal parts.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum MsgContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl MsgContent {
+    /// The text parts joined together; media is left out.
+    pub fn text(&self) -> String {
+        match self {
+            MsgContent::Text(text) => text.clone(),
+            MsgContent::Parts(parts) => parts
+                .iter()
+                .filter_map(|p| match p {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            MsgContent::Text(text) => text.is_empty(),
+            MsgContent::Parts(parts) => parts.is_empty(),
+        }
+    }
+
+    /// True when there is anything besides text (which needs a provider's native block format).
+    pub fn has_media(&self) -> bool {
+        matches!(self, MsgContent::Parts(parts) if parts.iter().any(|p| !matches!(p, ContentPart::Text { .. })))
+    }
+}
+
+impl Default for MsgContent {
+    fn default() -> Self {
+        MsgContent::Text(String::new())
+    }
+}
+
+impl From<String> for MsgContent {
+    fn from(text: String) -> Self {
+        MsgContent::Text(text)
+    }
+}
+
+impl From<&str> for MsgContent {
+    fn from(text: &str) -> Self {
+        MsgContent::Text(text.to_string())
+    }
+}
+
+impl From<Vec<ContentPart>> for MsgContent {
+    fn from(parts: Vec<ContentPart>) -> Self {
+        MsgContent::Parts(parts)
+    }
+}
+
+/// One block of a multimodal message. A backend that cannot take a part fails the call with
+/// `AiError::BadRequest` rather than sending the message without it.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ContentPart {
+    Text { text: String },
+    Image { source: MediaSource },
+    Document {
+        source: MediaSource, // PDF
+        #[serde(default)]
+        name: Option<String>,
+    },
+    Audio {
+        data: String,   // base64
+        format: String, // "wav" | "mp3"
+    },
+}
+
+/// Where media bytes come from.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MediaSource {
+    #[serde(rename_all = "camelCase")]
+    Url {
+        url: String,
+        #[serde(default)]
+        media_type: Option<String>, // some providers (Gemini) need it for remote files
+    },
+    #[serde(rename_all = "camelCase")]
+    Base64 { media_type: String, data: String }, // e.g. "image/png"
+}
+
+impl MediaSource {
+    /// `data:` url for base64 sources, the url itself otherwise.
+    pub fn to_url(&self) -> String {
+        match self {
+            MediaSource::Url { url, .. } => url.clone(),
+            MediaSource::Base64 { media_type, data } => format!("data:{media_type};base64,{data}"),
+        }
+    }
+}
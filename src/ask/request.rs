@@ -1,6 +1,12 @@
 use serde::{Deserialize, Serialize};
 
-use crate::ask::{msg::Msg, response::AskResponse};
+use crate::client::{ollama::OllamaOptions, openrouter::OpenRouterOptions, responses::ResponsesOptions};
+use crate::ask::{
+    msg::{CacheControl, Msg},
+    response::AskResponse,
+    schema::JsonSchemaSpec,
+    tool::{ToolCall, ToolChoice, ToolSpec},
+};
 
 /// Per-call generation knobs (leave unset to use server defaults).
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -9,7 +15,58 @@ pub struct AskOptions {
     pub temperature: Option<f32>,       // e.g., Some(0.2)
     pub max_output_tokens: Option<u32>, // e.g., Some(512)
     pub json_mode: Option<bool>,        // request strict JSON if supported
+    #[serde(default)]
+    pub json_schema: Option<JsonSchemaSpec>, // structured output; implies json_mode
     pub stream: Option<bool>,           // stream tokens (adapter may ignore for now)
+    #[serde(default)]
+    pub reasoning: Option<ReasoningOptions>, // reasoning effort / thinking budget for models that support it
+    #[serde(default)]
+    pub openrouter: Option<OpenRouterOptions>, // provider routing / model fallbacks (OpenRouter only)
+    #[serde(default)]
+    pub ollama: Option<OllamaOptions>,  // num_ctx, keep_alive, ... (Ollama only)
+    #[serde(default)]
+    pub responses: Option<ResponsesOptions>, // response chaining, reasoning summaries (OpenAI Responses API only)
+}
+
+/// How hard a reasoning model should think. OpenAI-style backends take `effort`; Anthropic takes
+/// `budget_tokens`, derived from `effort` when only that is set. Anthropic skips thinking on JSON
+/// requests and forced tool choices, which it does not allow together.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ReasoningOptions {
+    #[serde(default)]
+    pub effort: Option<ReasoningEffort>,
+    #[serde(default)]
+    pub budget_tokens: Option<u32>, // thinking tokens on top of `max_output_tokens` (Anthropic: at least 1024)
+}
+
+impl ReasoningOptions {
+    pub fn effort(effort: ReasoningEffort) -> Self {
+        Self { effort: Some(effort), budget_tokens: None }
+    }
+
+    pub fn budget(budget_tokens: u32) -> Self {
+        Self { effort: None, budget_tokens: Some(budget_tokens) }
+    }
+
+    /// Thinking budget in tokens: the explicit one, else one sized for `effort`.
+    pub fn budget_tokens(&self) -> Option<u32> {
+        self.budget_tokens.or(self.effort.map(|effort| match effort {
+            ReasoningEffort::Minimal => 1024,
+            ReasoningEffort::Low => 4096,
+            ReasoningEffort::Medium => 10_000,
+            ReasoningEffort::High => 32_000,
+        }))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Minimal,
+    Low,
+    Medium,
+    High,
 }
 
 /// The request shape your universal client expects.
@@ -18,27 +75,43 @@ pub struct AskOptions {
 pub struct AskRequest {
     #[serde(default)]
     pub system: Option<String>, // optional system instruction
+    #[serde(default)]
+    pub system_cache: Option<CacheControl>, // prompt cache breakpoint after the system prompt (Anthropic)
     pub messages: Vec<Msg>,     // recent window + summary, already packed
     #[serde(default)]
     pub options: AskOptions,    // per-call overrides
     #[serde(default)]
     pub context_refs: Vec<String>, // optional ids (conversation, project, doc)
+    #[serde(default)]
+    pub tools: Vec<ToolSpec>,   // tools the model may call
+    #[serde(default)]
+    pub tool_choice: Option<ToolChoice>, // provider default (auto) when unset
     pub provider: String,       // e.g., "openai" or "anthropic"
     pub model: String,          // e.g., "gpt-4o" or
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)] // `Complete` arrives once per stream; boxing it buys nothing
 pub enum AskChunk {
     /// Token/text delta for chat.
     Delta { text: String },
 
-    /// Tool call deltas are streamed; you must assemble them.
+    /// Tool call deltas are streamed; assemble them yourself or wrap the stream in
+    /// `assemble_tool_calls`. `tool_call_id` may be empty on continuation fragments.
     ToolCallDelta {
+        index: u32,
         tool_call_id: String,
         name: Option<String>,
         args_delta: Option<String>,
     },
 
+    /// A finished tool call (only emitted by `assemble_tool_calls`).
+    ToolCall(ToolCall),
+
+    /// Reasoning/thinking delta, kept apart from the answer text. Only providers that expose
+    /// their reasoning send these (OpenAI Chat Completions keeps it hidden).
+    Thinking { text: String },
+
     /// Provider emitted a role/content change (rare but possible).
     Role(String),
 
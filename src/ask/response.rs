@@ -1,16 +1,49 @@
 use serde::{Deserialize, Serialize};
 
+use crate::ask::msg::ThinkingBlock;
+use crate::ask::tool::ToolCall;
+
 /// Provider-agnostic response your app can rely on.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct AskResponse {
     pub text: String,                // final answer string (may be empty on tool calls)
     pub finish_reason: String,       // normalized: "stop" | "length" | "tool_call" | "content_filter" | "error"
     #[serde(default)]
     pub usage: Option<Usage>,        // token usage if available
-    pub latency_ms: u128,            // end-to-end latency measured by caller
+    #[serde(default)]
+    pub cost: Option<Cost>,          // priced from `usage` (see `PricingTable`); none for unpriced models
+    pub latency_ms: u128,            // end-to-end latency, including rate-limit waits and retries
+    #[serde(default)]
+    pub time_to_first_token_ms: Option<u128>, // streaming only: until the first text, thinking or tool call delta
+    #[serde(default)]
+    pub tokens_per_second: Option<f64>, // streaming only: completion tokens over the time after the first token
     #[serde(default)]
     pub provider_meta: serde_json::Value, // raw provider payload or fields for debugging
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,   // tools the model asked to run (finish_reason "tool_call")
+    #[serde(default)]
+    pub thinking: Vec<ThinkingBlock>, // reasoning the provider returned, in order
+    #[serde(default)]
+    pub provider: String,            // backend that answered, e.g. "Anthropic" (matters with fallbacks)
+    #[serde(default)]
+    pub model: String,               // model that answered as the provider reports it, else the one asked
+    #[serde(default)]
+    pub response_id: Option<String>, // provider's id for this response (Responses API: pass as `previous_response_id`)
+}
+
+impl AskResponse {
+    /// The readable thinking joined together; redacted blocks are left out.
+    pub fn thinking_text(&self) -> String {
+        self.thinking
+            .iter()
+            .filter_map(|block| match block {
+                ThinkingBlock::Thinking { text, .. } => Some(text.as_str()),
+                ThinkingBlock::Redacted { .. } => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 /// Normalized usage counters (best-effort; some providers may omit).
@@ -19,4 +52,34 @@ pub struct Usage {
     pub prompt_tokens: Option<u32>,
     pub completion_tokens: Option<u32>,
     pub total_tokens: Option<u32>,
+    #[serde(default)]
+    pub cached_tokens: Option<u32>,    // part of `prompt_tokens` read from the provider's prompt cache
+    #[serde(default)]
+    pub cache_write_tokens: Option<u32>, // part of `prompt_tokens` written to the prompt cache (Anthropic)
+    #[serde(default)]
+    pub cache_write_1h_tokens: Option<u32>, // part of `cache_write_tokens` kept for 1 hour, billed higher
+    #[serde(default)]
+    pub reasoning_tokens: Option<u32>, // part of `completion_tokens` spent on hidden reasoning
+}
+
+/// Dollar cost of a call (USD).
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Cost {
+    pub input: f64,        // uncached prompt tokens
+    pub output: f64,
+    pub cached_input: f64, // cache reads, billed at the cached rate
+    #[serde(default)]
+    pub cache_write: f64,  // cache writes, billed at the cache write rate
+    pub total: f64,
+}
+
+impl std::ops::AddAssign<&Cost> for Cost {
+    fn add_assign(&mut self, other: &Cost) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cached_input += other.cached_input;
+        self.cache_write += other.cache_write;
+        self.total += other.total;
+    }
 }
@@ -0,0 +1,173 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Shape the model's answer must follow (`AskOptions.json_schema`).
+///
+/// OpenAI-style backends send it as a `json_schema` response format, Anthropic as a forced tool
+/// call whose arguments become the response text.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonSchemaSpec {
+    pub name: String, // a-z, A-Z, 0-9, `_` and `-`, at most 64 chars
+    #[serde(default)]
+    pub description: Option<String>,
+    pub schema: Value, // root must be an object schema
+    #[serde(default = "strict_by_default")]
+    pub strict: bool, // OpenAI strict mode; the schema is tightened to what strict accepts
+}
+
+/// Deserialized specs match `new` / `of`, which opt into strict mode.
+fn strict_by_default() -> bool {
+    true
+}
+
+impl JsonSchemaSpec {
+    pub fn new(name: impl Into<String>, schema: Value) -> Self {
+        Self {
+            name: sanitize_name(&name.into()),
+            description: None,
+            schema,
+            strict: true,
+        }
+    }
+
+    /// Schema generated from `T`'s `JsonSchema` derive, named after the type.
+    pub fn of<T: JsonSchema>() -> Self {
+        let mut schema = schemars::schema_for!(T).to_value();
+        let description = schema["description"].as_str().map(str::to_string);
+        if let Some(obj) = schema.as_object_mut() {
+            obj.remove("$schema");
+        }
+        Self {
+            description,
+            ..Self::new(T::schema_name(), schema)
+        }
+    }
+
+    /// Prompt text for backends that cannot enforce the schema themselves.
+    pub fn instruction(&self) -> String {
+        format!("Respond only with JSON matching this JSON Schema:\n{}", self.schema)
+    }
+
+    /// The schema as OpenAI strict mode wants it: every object closed, every property required
+    /// (optional fields stay nullable), `oneOf` spelled `anyOf`, unsupported formats dropped.
+    pub fn strict_schema(&self) -> Value {
+        let mut schema = self.schema.clone();
+        tighten(&mut schema);
+        schema
+    }
+}
+
+/// String formats OpenAI strict mode accepts.
+const STRICT_FORMATS: &[&str] = &["date-time", "time", "date", "duration", "email", "hostname", "ipv4", "ipv6", "uuid"];
+
+fn tighten(schema: &mut Value) {
+    let Value::Object(obj) = schema else { return };
+    if obj.get("format").and_then(Value::as_str).is_some_and(|f| !STRICT_FORMATS.contains(&f)) {
+        obj.remove("format"); // e.g. schemars' "uint32"
+    }
+    if let Some(one_of) = obj.remove("oneOf") {
+        obj.insert("anyOf".to_string(), one_of);
+    }
+    if let Some(props) = obj.get("properties").and_then(Value::as_object) {
+        let required = props.keys().cloned().map(Value::String).collect();
+        obj.insert("required".to_string(), Value::Array(required));
+        obj.insert("additionalProperties".to_string(), Value::Bool(false));
+    }
+
+    // Only recurse where a subschema sits; property names and `default`/`enum` values are data
+    for key in ["properties", "$defs", "definitions"] {
+        if let Some(Value::Object(subschemas)) = obj.get_mut(key) {
+            subschemas.values_mut().for_each(tighten);
+        }
+    }
+    for key in ["items", "anyOf", "allOf"] {
+        match obj.get_mut(key) {
+            Some(Value::Array(subschemas)) => subschemas.iter_mut().for_each(tighten),
+            Some(subschema) => tighten(subschema),
+            None => {}
+        }
+    }
+}
+
+fn sanitize_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .take(64)
+        .collect();
+    if name.is_empty() { "response".to_string() } else { name }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn strict_schema_closes_objects_and_requires_every_property() {
+        let spec = JsonSchemaSpec::new("reply", json!({
+            "type": "object",
+            "properties": {
+                "properties": { "type": "object", "properties": { "x": { "type": "integer" } } },
+                "age": { "type": "integer", "format": "uint32" },
+                "born": { "type": "string", "format": "date" },
+            },
+        }));
+        let schema = spec.strict_schema();
+
+        assert_eq!(schema["required"], json!(["age", "born", "properties"]));
+        assert_eq!(schema["additionalProperties"], false);
+        // A property named `properties` is a field, not a subschema map
+        let field = &schema["properties"]["properties"];
+        assert_eq!(field["required"], json!(["x"]));
+        assert_eq!(field["additionalProperties"], false);
+        assert!(schema["properties"]["age"].get("format").is_none());
+        assert_eq!(schema["properties"]["born"]["format"], "date");
+    }
+
+    #[test]
+    fn strict_schema_rewrites_one_of_and_reaches_defs() {
+        let spec = JsonSchemaSpec::new("reply", json!({
+            "type": "object",
+            "properties": { "pet": { "oneOf": [{ "$ref": "#/$defs/Cat" }, { "type": "null" }] } },
+            "$defs": {
+                "Cat": { "type": "object", "properties": { "lives": { "type": "integer", "format": "uint8" } } },
+            },
+        }));
+        let schema = spec.strict_schema();
+
+        let pet = &schema["properties"]["pet"];
+        assert!(pet.get("oneOf").is_none());
+        assert_eq!(pet["anyOf"].as_array().map(Vec::len), Some(2));
+        let cat = &schema["$defs"]["Cat"];
+        assert_eq!(cat["required"], json!(["lives"]));
+        assert_eq!(cat["additionalProperties"], false);
+        assert!(cat["properties"]["lives"].get("format").is_none());
+    }
+
+    #[test]
+    fn strict_schema_leaves_enum_and_default_values_alone() {
+        let data = json!({ "type": "object", "properties": { "a": { "type": "string" } }, "format": "uint32" });
+        let spec = JsonSchemaSpec::new("reply", json!({
+            "type": "object",
+            "properties": {
+                "mode": { "enum": [data.clone()], "default": data.clone() },
+            },
+        }));
+        let schema = spec.strict_schema();
+
+        assert_eq!(schema["properties"]["mode"]["enum"], json!([data.clone()]));
+        assert_eq!(schema["properties"]["mode"]["default"], data);
+    }
+
+    #[test]
+    fn deserialized_spec_is_strict_unless_told_otherwise() {
+        let spec: JsonSchemaSpec = serde_json::from_value(json!({ "name": "reply", "schema": {} })).unwrap();
+        assert!(spec.strict);
+        let spec: JsonSchemaSpec = serde_json::from_value(json!({ "name": "reply", "schema": {}, "strict": false })).unwrap();
+        assert!(!spec.strict);
+    }
+}
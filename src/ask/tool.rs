@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+
+use async_stream::try_stream;
+use futures_core::Stream;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{ask::request::AskChunk, error::AiError, util::parse_tool_args};
+
+/// A tool the model may call. `parameters` is a JSON Schema object describing the arguments.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub parameters: Value,
+}
+
+impl ToolSpec {
+    pub fn new(name: impl Into<String>, description: impl Into<String>, parameters: Value) -> Self {
+        Self {
+            name: name.into(),
+            description: Some(description.into()),
+            parameters,
+        }
+    }
+}
+
+/// How the model should pick among the declared tools.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolChoice {
+    Auto,     // model decides (provider default when tools are present)
+    None,     // never call a tool
+    Required, // must call at least one tool
+    Tool(String), // must call this specific tool
+}
+
+/// A finished tool invocation requested by the model.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: Value, // parsed JSON; raw string if the model emitted invalid JSON
+}
+
+/// Buffers streamed `ToolCallDelta` fragments until each call is complete.
+///
+/// Fragments are matched by `index`, falling back to `tool_call_id` when the index is reused
+/// for a different call. Providers stream calls in order, so a call closes as soon as a
+/// fragment for a later index arrives, or when the stream completes.
+#[derive(Debug, Default)]
+pub struct ToolCallAssembler {
+    pending: BTreeMap<u32, PartialToolCall>,
+    finished: Vec<ToolCall>,
+}
+
+#[derive(Debug, Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    args: String,
+}
+
+impl PartialToolCall {
+    fn finish(self) -> ToolCall {
+        ToolCall {
+            id: self.id,
+            name: self.name,
+            arguments: parse_tool_args(&self.args),
+        }
+    }
+}
+
+impl ToolCallAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one delta; returns any calls that closed because of it.
+    pub fn push(&mut self, index: u32, tool_call_id: &str, name: Option<&str>, args_delta: Option<&str>) -> Vec<ToolCall> {
+        let mut closed = Vec::new();
+
+        // Same index but a different id: the earlier call is done
+        if let Some(existing) = self.pending.get(&index)
+            && !tool_call_id.is_empty()
+            && !existing.id.is_empty()
+            && existing.id != tool_call_id
+            && let Some(done) = self.pending.remove(&index)
+        {
+            closed.push(done.finish());
+        }
+
+        // Any call at a lower index will not receive more fragments
+        let earlier: Vec<u32> = self.pending.range(..index).map(|(i, _)| *i).collect();
+        for i in earlier {
+            if let Some(done) = self.pending.remove(&i) {
+                closed.push(done.finish());
+            }
+        }
+
+        let entry = self.pending.entry(index).or_default();
+        if entry.id.is_empty() {
+            entry.id = tool_call_id.to_string();
+        }
+        if let Some(n) = name
+            && entry.name.is_empty()
+        {
+            entry.name = n.to_string();
+        }
+        if let Some(a) = args_delta {
+            entry.args.push_str(a);
+        }
+
+        self.finished.extend(closed.iter().cloned());
+        closed
+    }
+
+    /// Close every call still open; returns them in index order.
+    pub fn flush(&mut self) -> Vec<ToolCall> {
+        let closed: Vec<ToolCall> = std::mem::take(&mut self.pending)
+            .into_values()
+            .map(PartialToolCall::finish)
+            .collect();
+        self.finished.extend(closed.iter().cloned());
+        closed
+    }
+
+    /// Every call assembled so far, in the order they closed.
+    pub fn tool_calls(&self) -> &[ToolCall] {
+        &self.finished
+    }
+}
+
+/// Wraps an `ask_stream` output: passes every chunk through, emits `AskChunk::ToolCall` as each
+/// streamed call closes, and places the assembled calls on the final `AskChunk::Complete`.
+pub fn assemble_tool_calls<'a, S>(stream: S) -> impl Stream<Item = Result<AskChunk, AiError>> + Send + 'a
+where
+    S: Stream<Item = Result<AskChunk, AiError>> + Send + 'a,
+{
+    try_stream! {
+        let mut stream = Box::pin(stream);
+        let mut assembler = ToolCallAssembler::new();
+
+        while let Some(chunk) = stream.next().await {
+            match chunk? {
+                AskChunk::ToolCallDelta { index, tool_call_id, name, args_delta } => {
+                    let closed = assembler.push(index, &tool_call_id, name.as_deref(), args_delta.as_deref());
+                    yield AskChunk::ToolCallDelta { index, tool_call_id, name, args_delta };
+                    for tc in closed {
+                        yield AskChunk::ToolCall(tc);
+                    }
+                }
+                AskChunk::Complete(mut resp) => {
+                    for tc in assembler.flush() {
+                        yield AskChunk::ToolCall(tc);
+                    }
+                    if resp.tool_calls.is_empty() {
+                        resp.tool_calls = assembler.tool_calls().to_vec();
+                    }
+                    yield AskChunk::Complete(resp);
+                }
+                other => yield other,
+            }
+        }
+
+        // Stream ended without a Complete; still hand out what we have
+        for tc in assembler.flush() {
+            yield AskChunk::ToolCall(tc);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn push_buffers_fragments_until_flush() {
+        let mut assembler = ToolCallAssembler::new();
+        assert!(assembler.push(0, "call_1", Some("get_weather"), Some("{\"city\":")).is_empty());
+        assert!(assembler.push(0, "", None, Some("\"Oslo\"}")).is_empty());
+        assert!(assembler.tool_calls().is_empty());
+
+        let closed = assembler.flush();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].id, "call_1");
+        assert_eq!(closed[0].name, "get_weather");
+        assert_eq!(closed[0].arguments, json!({ "city": "Oslo" }));
+        assert_eq!(assembler.tool_calls().len(), 1);
+        assert!(assembler.flush().is_empty());
+    }
+
+    #[test]
+    fn push_closes_calls_at_lower_indexes() {
+        let mut assembler = ToolCallAssembler::new();
+        assembler.push(0, "call_1", Some("a"), Some("{}"));
+        let closed = assembler.push(1, "call_2", Some("b"), None);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].id, "call_1");
+
+        let closed = assembler.flush();
+        assert_eq!(closed[0].id, "call_2");
+        assert_eq!(closed[0].arguments, json!({}));
+        let ids: Vec<&str> = assembler.tool_calls().iter().map(|tc| tc.id.as_str()).collect();
+        assert_eq!(ids, ["call_1", "call_2"]);
+    }
+
+    #[test]
+    fn push_closes_a_call_when_its_index_is_reused() {
+        let mut assembler = ToolCallAssembler::new();
+        assembler.push(0, "call_1", Some("a"), Some("not json"));
+        let closed = assembler.push(0, "call_2", Some("b"), Some("{\"x\":1}"));
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].arguments, json!("not json"));
+
+        let closed = assembler.flush();
+        assert_eq!(closed[0].name, "b");
+        assert_eq!(closed[0].arguments, json!({ "x": 1 }));
+    }
+}
@@ -1,182 +1,591 @@
-use anthropic_sdk::Client;
 use futures_core::Stream;
+use futures_util::StreamExt;
 use serde_json::{json, Value};
-use tokio::sync::mpsc;
 use async_stream::try_stream;
 
 use crate::{
     ask::{
         config::AskConfig,
-        msg::{Msg, Role},
+        msg::{CacheControl, ContentPart, MediaSource, Msg, MsgContent, Role, ThinkingBlock},
         request::{AskChunk, AskRequest},
         response::{AskResponse, Usage},
+        tool::{ToolCall, ToolChoice},
     },
-    error::AiError,
+    client::{ratelimit, sse::sse_events},
+    error::{AiError, ErrorInfo},
+    util::{endpoint, error_info, get_http_client, map_reqwest_err, parse_json, parse_tool_args, unsupported_part},
 };
 
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// JSON mode: the assistant turn is prefilled with this so the model continues an object.
+const JSON_PREFILL: &str = "{";
+const JSON_INSTRUCTION: &str = "Respond only with a single valid JSON object. No prose, no code fences.";
+
 pub struct AnthropicApi;
 
 impl AnthropicApi {
-    /// Non-streaming: builds once and collects full text.
+    /// Non-streaming: one Messages API call, parsed into a full `AskResponse`.
     pub async fn ask(
         config: AskConfig,
         request: &AskRequest,
     ) -> Result<AskResponse, AiError> {
-        let messages = build_anthropic_messages(request);
-
-        let mut builder = Client::new()
-            .auth(&config.api_key)
-            .model(&config.model)
-            .max_tokens(request.options.max_output_tokens.unwrap_or(1024) as i32)
-            .messages(&json!(messages));
+        let resp = send_messages(&config, request, false).await?;
+        let body = resp.bytes().await.map_err(map_reqwest_err)?;
+        let message: Value = serde_json::from_slice(&body).map_err(|e| AiError::Json(e.to_string()))?;
 
-        if let Some(system) = &request.system {
-            builder = builder.system(system);
+        let mut response = parse_anthropic_message(message);
+        if let Some(spec) = &request.options.json_schema {
+            schema_call_to_text(&mut response, &spec.name);
         }
-        if let Some(t) = request.options.temperature {
-            builder = builder.temperature(t);
+        if json_prefill(request) {
+            finish_json_mode(&mut response)?;
         }
-
-        let req = builder.build().map_err(|e| AiError::Provider(e.to_string()))?;
-
-        // Accumulate streamed content from the callback into a buffer
-        let acc = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
-        let acc2 = acc.clone();
-
-        req.execute(move |content| {
-            let acc = acc2.clone();
-            async move {
-                if let Ok(mut s) = acc.lock() {
-                    s.push_str(&content);
-                }
-            }
-        })
-        .await
-        .map_err(|e| AiError::Provider(e.to_string()))?;
-
-        let final_text = acc.lock().unwrap().clone();
-
-        Ok(AskResponse {
-            text: final_text,
-            finish_reason: "stop".to_string(), // SDK doesn't expose finish_reason here
-            usage: None,                        // not available here; fill upstream if needed
-            latency_ms: 0,
-            provider_meta: Value::Null,
-        })
+        Ok(response)
     }
 
-    /// Streaming: exposes a Stream of AskChunk::Delta then AskChunk::Complete.
+    /// Streaming: parses the Messages SSE events into `AskChunk`s, ending with `AskChunk::Complete`
+    /// (or an error when the stream stops before `message_stop`).
     pub async fn ask_stream(
         config: AskConfig,
         request: &AskRequest,
     ) -> Result<impl Stream<Item = Result<AskChunk, AiError>> + Send, AiError> {
-        
-
-        let messages = build_anthropic_messages(request);
+        let resp = send_messages(&config, request, true).await?;
+        let mut events = Box::pin(sse_events(resp));
+        let schema_tool = request.options.json_schema.as_ref().map(|spec| spec.name.clone());
+        let prefill = json_prefill(request);
 
-        let mut builder = Client::new()
-            .auth(&config.api_key)
-            .model(&config.model)
-            .max_tokens(request.options.max_output_tokens.unwrap_or(1024) as i32)
-            .messages(&json!(messages));
-
-        if let Some(system) = &request.system {
-            builder = builder.system(system);
-        }
-        if let Some(t) = request.options.temperature {
-            builder = builder.temperature(t);
-        }
+        let s = try_stream! {
+            let mut message = MessageAssembler::default();
+            // The prefilled `{` goes out with the first generated text, not ahead of it
+            let mut prefill_pending = prefill;
+            let mut stopped = false;
 
-        let req = builder.build().map_err(|e| AiError::Provider(e.to_string()))?;
-
-        // Bridge SDK callback -> Stream via channel
-        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
-        let (err_tx, mut err_rx) = mpsc::unbounded_channel::<AiError>();
-
-        // Spawn producer
-        let handle = tokio::spawn({
-            let tx = tx.clone();
-            async move {
-                let res = req
-                    .execute(move |content| {
-                        let tx = tx.clone();
-                        async move {
-                            let _ = tx.send(content.to_string());
-                        }
-                    })
-                    .await;
-                if let Err(e) = res {
-                    let _ = err_tx.send(AiError::Provider(e.to_string()));
+            while let Some(event) = events.next().await {
+                let event = event?;
+                if event.data.is_empty() {
+                    continue;
                 }
-                // dropping tx closes channel
-            }
-        });
+                let data: Value = serde_json::from_str(&event.data).map_err(|e| AiError::Json(e.to_string()))?;
 
-        let s = try_stream! {
-            let mut full_text = String::new();
-            let mut stream_err: Option<AiError> = None;
-
-            loop {
-                tokio::select! {
-                    Some(err) = err_rx.recv() => {
-                        let _ = handle.await;
-                        stream_err = Some(err);
-                        break;
+                match data["type"].as_str().unwrap_or_default() {
+                    "message_start" => {
+                        message.start(&data["message"]);
+                    }
+                    "content_block_start" => {
+                        let index = data["index"].as_u64().unwrap_or_default() as usize;
+                        let block = &data["content_block"];
+                        message.start_block(index, block);
+                        if block["type"] == "tool_use" && !is_schema_tool(&schema_tool, block) {
+                            yield AskChunk::ToolCallDelta {
+                                index: index as u32,
+                                tool_call_id: block["id"].as_str().unwrap_or_default().to_string(),
+                                name: block["name"].as_str().map(str::to_string),
+                                args_delta: None,
+                            };
+                        }
+                    }
+                    "content_block_delta" => {
+                        let index = data["index"].as_u64().unwrap_or_default() as usize;
+                        let delta = &data["delta"];
+                        message.apply_delta(index, delta);
+                        match delta["type"].as_str().unwrap_or_default() {
+                            "text_delta" => {
+                                let mut text = delta["text"].as_str().unwrap_or_default().to_string();
+                                if std::mem::take(&mut prefill_pending) {
+                                    text.insert_str(0, JSON_PREFILL);
+                                }
+                                yield AskChunk::Delta { text };
+                            }
+                            "thinking_delta" => {
+                                yield AskChunk::Thinking { text: delta["thinking"].as_str().unwrap_or_default().to_string() };
+                            }
+                            // The forced structured-output call streams as plain text
+                            "input_json_delta" if message.block(index).is_some_and(|b| is_schema_tool(&schema_tool, b)) => {
+                                yield AskChunk::Delta { text: delta["partial_json"].as_str().unwrap_or_default().to_string() };
+                            }
+                            "input_json_delta" => {
+                                yield AskChunk::ToolCallDelta {
+                                    index: index as u32,
+                                    tool_call_id: message.block_id(index),
+                                    name: None,
+                                    args_delta: delta["partial_json"].as_str().map(str::to_string),
+                                };
+                            }
+                            _ => {}
+                        }
                     }
-                    Some(delta) = rx.recv() => {
-                        full_text.push_str(&delta);
-                        yield AskChunk::Delta { text: delta };
+                    "content_block_stop" => {
+                        let index = data["index"].as_u64().unwrap_or_default() as usize;
+                        message.stop_block(index);
                     }
-                    else => {
-                        let _ = handle.await;
-                        let resp = AskResponse {
-                            text: full_text,
-                            finish_reason: "stop".to_string(),
-                            usage: None,
-                            latency_ms: 0,
-                            provider_meta: Value::Null,
-                        };
-                        yield AskChunk::Complete(resp);
+                    "message_delta" => message.apply_message_delta(&data),
+                    "message_stop" => {
+                        stopped = true;
                         break;
                     }
+                    "error" => Err(anthropic_error(ErrorInfo::default(), &data))?,
+                    _ => {} // ping and future event types
                 }
             }
 
-            if let Some(err) = stream_err {
-                Err::<(), AiError>(err)?;
+            // No `message_stop`: the body was cut off mid-answer
+            if !stopped {
+                Err(AiError::Http("stream ended before message_stop".to_string()))?;
             }
+
+            let mut response = parse_anthropic_message(message.finish());
+            if let Some(name) = &schema_tool {
+                schema_call_to_text(&mut response, name);
+            }
+            if prefill {
+                finish_json_mode(&mut response)?;
+            }
+            yield AskChunk::Complete(response);
         };
 
         Ok(s)
     }
 
-    // keep this exactly as you had it
-    pub async fn get_models(_config: &AskConfig) -> Result<Value, AiError> {
-        let models = vec![
-            "default",
-            "sonnet",
-            "opus",
-            "haiku",
-            "sonnet[1m]",
-            "opusplan"
-        ];
+    /// Model ids available to this key, newest first.
+    pub async fn get_models(config: &AskConfig) -> Result<Value, AiError> {
+        let http = get_http_client(config)?;
+        let resp = http
+            .get(endpoint(config, "models?limit=1000"))
+            .header("x-api-key", &config.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .timeout(config.request_timeout)
+            .send()
+            .await
+            .map_err(map_reqwest_err)?;
+
+        if !resp.status().is_success() {
+            let info = error_info(&resp);
+            let body: Value = resp.json().await.unwrap_or(Value::Null);
+            return Err(anthropic_error(info, &body));
+        }
+        let body: Value = resp.json().await.map_err(|e| AiError::Json(e.to_string()))?;
+
+        let models = body["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m["id"].as_str().map(str::to_string))
+            .collect::<Vec<String>>();
         Ok(json!(models))
     }
 }
 
-fn build_anthropic_messages(request: &AskRequest) -> Vec<Value> {
-    // System goes in `.system(...)`; tool messages will map later to tool blocks/tool_result.
-    let mut messages = Vec::with_capacity(request.messages.len());
+/// POST `{config.url}/messages` and fail fast on a non-success status. Timeouts work as in
+/// `openai::send_chat`.
+async fn send_messages(config: &AskConfig, request: &AskRequest, stream: bool) -> Result<reqwest::Response, AiError> {
+    let http = get_http_client(config)?;
+    let body = build_anthropic_body(config, request, stream)?;
+
+    let mut builder = http
+        .post(endpoint(config, "messages"))
+        .header("x-api-key", &config.api_key)
+        .header("anthropic-version", ANTHROPIC_VERSION)
+        .json(&body);
+    if !stream {
+        builder = builder.timeout(config.request_timeout);
+    }
+
+    let resp = builder.send().await.map_err(map_reqwest_err)?;
+    ratelimit::observe_headers(config, resp.headers());
+    if !resp.status().is_success() {
+        let info = error_info(&resp);
+        let body: Value = resp.json().await.unwrap_or(Value::Null);
+        return Err(anthropic_error(info, &body));
+    }
+    Ok(resp)
+}
+
+/// Map an Anthropic error payload (`{"type":"error","error":{...}}`) to `AiError`.
+fn anthropic_error(mut info: ErrorInfo, body: &Value) -> AiError {
+    info.error_type = body["error"]["type"].as_str().map(str::to_string);
+    info.message = body["error"]["message"].as_str().unwrap_or("unknown error").to_string();
+    if info.request_id.is_none() {
+        info.request_id = body["request_id"].as_str().map(str::to_string);
+    }
+    info.into_error()
+}
+
+fn build_anthropic_body(config: &AskConfig, request: &AskRequest, stream: bool) -> Result<Value, AiError> {
+    let thinking_budget = thinking_budget(request);
+    let mut body = json!({
+        "model": config.model,
+        "max_tokens": request.options.max_output_tokens.unwrap_or(1024) + thinking_budget.unwrap_or(0),
+        "messages": build_anthropic_messages(request)?,
+        "stream": stream,
+    });
+    if let Some(budget) = thinking_budget {
+        body["thinking"] = json!({ "type": "enabled", "budget_tokens": budget });
+    }
+
+    let system = match (&request.system, json_prefill(request)) {
+        (Some(sys), true) => Some(format!("{sys}\n\n{JSON_INSTRUCTION}")),
+        (None, true) => Some(JSON_INSTRUCTION.to_string()),
+        (sys, false) => sys.clone(),
+    };
+    if let Some(system) = system {
+        body["system"] = with_cache_control(json!(system), request.system_cache.as_ref());
+    }
+    if json_prefill(request)
+        && let Some(messages) = body["messages"].as_array_mut()
+    {
+        messages.push(json!({ "role": "assistant", "content": JSON_PREFILL }));
+    }
+    // Extended thinking only runs at the default temperature
+    if let Some(t) = request.options.temperature.filter(|_| thinking_budget.is_none()) {
+        body["temperature"] = json!(t);
+    }
+    if let Some(spec) = &request.options.json_schema {
+        // No native structured output: force a call to a tool whose input is the answer
+        let mut tools = build_anthropic_tools(request);
+        if let Some(tools) = tools.as_array_mut() {
+            tools.push(json!({
+                "name": spec.name,
+                "description": spec.description.as_deref().unwrap_or("Respond with the requested JSON."),
+                "input_schema": spec.schema,
+            }));
+        }
+        body["tools"] = tools;
+        body["tool_choice"] = json!({ "type": "tool", "name": spec.name });
+    } else if !request.tools.is_empty() {
+        body["tools"] = build_anthropic_tools(request);
+        if let Some(choice) = &request.tool_choice {
+            body["tool_choice"] = match choice {
+                ToolChoice::Auto => json!({ "type": "auto" }),
+                ToolChoice::None => json!({ "type": "none" }),
+                ToolChoice::Required => json!({ "type": "any" }),
+                ToolChoice::Tool(name) => json!({ "type": "tool", "name": name }),
+            };
+        }
+    }
+    Ok(body)
+}
+
+fn build_anthropic_tools(request: &AskRequest) -> Value {
+    request
+        .tools
+        .iter()
+        .map(|t| {
+            let mut tool = json!({ "name": t.name, "input_schema": t.parameters });
+            if let Some(desc) = &t.description {
+                tool["description"] = json!(desc);
+            }
+            tool
+        })
+        .collect()
+}
+
+fn build_anthropic_messages(request: &AskRequest) -> Result<Vec<Value>, AiError> {
+    // System goes in the top-level `system` field; tool results become `tool_result` blocks on a user turn.
+    let mut messages: Vec<Value> = Vec::with_capacity(request.messages.len());
     for m in &request.messages {
         match m {
             Msg { role: Role::User, content, .. } => {
+                let content = with_cache_control(anthropic_content(content)?, m.cache.as_ref());
                 messages.push(json!({ "role": "user", "content": content }));
             }
+            Msg { role: Role::Assistant, content, tool_calls, thinking, .. } if !tool_calls.is_empty() || !thinking.is_empty() => {
+                let mut blocks = Vec::with_capacity(thinking.len() + tool_calls.len() + 1);
+                // Thinking has to lead the turn, exactly as it was returned
+                for block in thinking {
+                    blocks.push(match block {
+                        ThinkingBlock::Thinking { text, signature } => {
+                            json!({ "type": "thinking", "thinking": text, "signature": signature })
+                        }
+                        ThinkingBlock::Redacted { data } => json!({ "type": "redacted_thinking", "data": data }),
+                    });
+                }
+                if !content.is_empty() {
+                    blocks.push(json!({ "type": "text", "text": content.text() }));
+                }
+                for tc in tool_calls {
+                    blocks.push(json!({
+                        "type": "tool_use",
+                        "id": tc.id,
+                        "name": tc.name,
+                        "input": tc.arguments,
+                    }));
+                }
+                let content = with_cache_control(json!(blocks), m.cache.as_ref());
+                messages.push(json!({ "role": "assistant", "content": content }));
+            }
             Msg { role: Role::Assistant, content, .. } => {
+                let content = with_cache_control(json!(content.text()), m.cache.as_ref());
                 messages.push(json!({ "role": "assistant", "content": content }));
             }
-            Msg { role: Role::Tool, .. } | Msg { role: Role::System, .. } => {}
+            Msg { role: Role::Tool, content, .. } => {
+                let mut block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": m.tool_call_id(),
+                    "content": anthropic_content(content)?,
+                });
+                if let Some(cache) = &m.cache {
+                    block["cache_control"] = anthropic_cache_control(cache);
+                }
+                // Parallel tool results must share a single user turn
+                match messages.last_mut() {
+                    Some(last) if is_tool_result_turn(last) => {
+                        if let Some(blocks) = last["content"].as_array_mut() {
+                            blocks.push(block);
+                        }
+                    }
+                    _ => messages.push(json!({ "role": "user", "content": [block] })),
+                }
+            }
+            Msg { role: Role::System, .. } => {}
         }
     }
-    messages
+    Ok(messages)
+}
+
+/// Plain text stays a string; parts become Messages API content blocks.
+fn anthropic_content(content: &MsgContent) -> Result<Value, AiError> {
+    let MsgContent::Parts(parts) = content else {
+        return Ok(json!(content.text()));
+    };
+    let source = |source: &MediaSource| match source {
+        MediaSource::Base64 { media_type, data } => json!({ "type": "base64", "media_type": media_type, "data": data }),
+        MediaSource::Url { url, .. } => json!({ "type": "url", "url": url }),
+    };
+    parts
+        .iter()
+        .map(|part| match part {
+            ContentPart::Text { text } => Ok(json!({ "type": "text", "text": text })),
+            ContentPart::Image { source: src } => Ok(json!({ "type": "image", "source": source(src) })),
+            ContentPart::Document { source: src, name } => {
+                let mut block = json!({ "type": "document", "source": source(src) });
+                if let Some(name) = name {
+                    block["title"] = json!(name);
+                }
+                Ok(block)
+            }
+            ContentPart::Audio { .. } => Err(unsupported_part("the Anthropic Messages API", "audio")),
+        })
+        .collect()
+}
+
+/// Put a cache breakpoint on the last block of `content` (plain text becomes a single text block).
+fn with_cache_control(content: Value, cache: Option<&CacheControl>) -> Value {
+    let Some(cache) = cache else {
+        return content;
+    };
+    let mut blocks = match content {
+        Value::String(text) => json!([{ "type": "text", "text": text }]),
+        blocks => blocks,
+    };
+    if let Some(last) = blocks.as_array_mut().and_then(|b| b.last_mut()) {
+        last["cache_control"] = anthropic_cache_control(cache);
+    }
+    blocks
+}
+
+fn anthropic_cache_control(cache: &CacheControl) -> Value {
+    let mut control = json!({ "type": "ephemeral" });
+    if let Some(ttl) = cache.ttl {
+        control["ttl"] = json!(ttl);
+    }
+    control
+}
+
+fn is_tool_result_turn(message: &Value) -> bool {
+    message["role"] == "user"
+        && message["content"]
+            .as_array()
+            .is_some_and(|blocks| blocks.iter().all(|b| b["type"] == "tool_result"))
+}
+
+/// Rebuilds the full Messages API response object from stream events so that streaming
+/// and non-streaming calls share `parse_anthropic_message`.
+#[derive(Default)]
+struct MessageAssembler {
+    message: Value,
+    blocks: Vec<Value>,
+    partial_json: Vec<String>,
+}
+
+impl MessageAssembler {
+    fn start(&mut self, message: &Value) {
+        self.message = message.clone();
+    }
+
+    fn start_block(&mut self, index: usize, block: &Value) {
+        if self.blocks.len() <= index {
+            self.blocks.resize(index + 1, Value::Null);
+            self.partial_json.resize(index + 1, String::new());
+        }
+        self.blocks[index] = block.clone();
+    }
+
+    fn block(&self, index: usize) -> Option<&Value> {
+        self.blocks.get(index)
+    }
+
+    fn block_id(&self, index: usize) -> String {
+        self.blocks
+            .get(index)
+            .and_then(|b| b["id"].as_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn apply_delta(&mut self, index: usize, delta: &Value) {
+        let Some(block) = self.blocks.get_mut(index) else { return };
+        let append = |block: &mut Value, key: &str, piece: &Value| {
+            let mut s = block[key].as_str().unwrap_or_default().to_string();
+            s.push_str(piece.as_str().unwrap_or_default());
+            block[key] = Value::String(s);
+        };
+        match delta["type"].as_str().unwrap_or_default() {
+            "text_delta" => append(block, "text", &delta["text"]),
+            "thinking_delta" => append(block, "thinking", &delta["thinking"]),
+            "signature_delta" => append(block, "signature", &delta["signature"]),
+            "input_json_delta" => self.partial_json[index].push_str(delta["partial_json"].as_str().unwrap_or_default()),
+            _ => {}
+        }
+    }
+
+    fn stop_block(&mut self, index: usize) {
+        if let Some(block) = self.blocks.get_mut(index)
+            && block["type"] == "tool_use"
+        {
+            block["input"] = parse_tool_args(&self.partial_json[index]);
+        }
+    }
+
+    fn apply_message_delta(&mut self, data: &Value) {
+        if let Some(delta) = data["delta"].as_object() {
+            for (k, v) in delta {
+                self.message[k] = v.clone();
+            }
+        }
+        // message_delta usage counters are cumulative; overlay them on message_start's
+        if let Some(usage) = data["usage"].as_object() {
+            for (k, v) in usage {
+                if !v.is_null() {
+                    self.message["usage"][k] = v.clone();
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> Value {
+        self.message["content"] = Value::Array(self.blocks.into_iter().filter(|b| !b.is_null()).collect());
+        self.message
+    }
+}
+
+/// Parse a Messages API response object into text, tool calls, finish reason and usage.
+fn parse_anthropic_message(message: Value) -> AskResponse {
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    let mut thinking = Vec::new();
+    for block in message["content"].as_array().into_iter().flatten() {
+        match block["type"].as_str() {
+            Some("text") => text.push_str(block["text"].as_str().unwrap_or_default()),
+            Some("thinking") => thinking.push(ThinkingBlock::Thinking {
+                text: block["thinking"].as_str().unwrap_or_default().to_string(),
+                signature: block["signature"].as_str().map(str::to_string),
+            }),
+            Some("redacted_thinking") => thinking.push(ThinkingBlock::Redacted {
+                data: block["data"].as_str().unwrap_or_default().to_string(),
+            }),
+            Some("tool_use") => tool_calls.push(ToolCall {
+                id: block["id"].as_str().unwrap_or_default().to_string(),
+                name: block["name"].as_str().unwrap_or_default().to_string(),
+                arguments: block["input"].clone(),
+            }),
+            _ => {}
+        }
+    }
+
+    let finish_reason = stop_reason_str(message["stop_reason"].as_str().unwrap_or("end_turn")).to_string();
+
+    let usage = message.get("usage").filter(|u| u.is_object()).map(|u| {
+        let count = |k: &str| u[k].as_u64().map(|n| n as u32);
+        let cache_read = count("cache_read_input_tokens");
+        let cache_write = count("cache_creation_input_tokens");
+        // `input_tokens` excludes cache reads and writes; count them in the prompt like OpenAI does
+        let prompt = count("input_tokens").map(|n| n + cache_read.unwrap_or(0) + cache_write.unwrap_or(0));
+        let completion = count("output_tokens");
+        Usage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: prompt.zip(completion).map(|(p, c)| p + c),
+            cached_tokens: cache_read,
+            cache_write_tokens: cache_write,
+            cache_write_1h_tokens: u["cache_creation"]["ephemeral_1h_input_tokens"].as_u64().map(|n| n as u32),
+            ..Default::default()
+        }
+    });
+
+    AskResponse {
+        text,
+        finish_reason,
+        usage,
+        latency_ms: 0,
+        response_id: message["id"].as_str().map(str::to_string),
+        model: message["model"].as_str().unwrap_or_default().to_string(),
+        provider_meta: message,
+        tool_calls,
+        thinking,
+        ..Default::default()
+    }
+}
+
+/// Extended thinking budget, when the request asks for reasoning. Anthropic rejects thinking
+/// together with a prefilled answer or a forced tool, so JSON requests and forced tool choices
+/// run without it.
+fn thinking_budget(request: &AskRequest) -> Option<u32> {
+    let forced_tool = request.options.json_schema.is_some()
+        || matches!(request.tool_choice, Some(ToolChoice::Required | ToolChoice::Tool(_)));
+    if forced_tool || json_prefill(request) {
+        return None;
+    }
+    request.options.reasoning.as_ref().and_then(|r| r.budget_tokens())
+}
+
+/// Plain JSON mode has no native switch; a `json_schema` request uses the forced tool instead.
+fn json_prefill(request: &AskRequest) -> bool {
+    request.options.json_mode.unwrap_or(false) && request.options.json_schema.is_none()
+}
+
+/// Restore the prefilled `{` and reject answers that are not JSON.
+fn finish_json_mode(response: &mut AskResponse) -> Result<(), AiError> {
+    if !response.tool_calls.is_empty() {
+        return Ok(());
+    }
+    response.text.insert_str(0, JSON_PREFILL);
+    parse_json::<Value>(&response.text).map_err(AiError::Json)?;
+    Ok(())
+}
+
+fn is_schema_tool(schema_tool: &Option<String>, block: &Value) -> bool {
+    schema_tool.as_deref().is_some_and(|name| block["type"] == "tool_use" && block["name"] == name)
+}
+
+/// Turn the forced structured-output tool call back into a plain JSON text answer.
+fn schema_call_to_text(response: &mut AskResponse, name: &str) {
+    let Some(pos) = response.tool_calls.iter().position(|tc| tc.name == name) else { return };
+    let call = response.tool_calls.remove(pos);
+    response.text = match call.arguments {
+        Value::String(raw) => raw, // invalid JSON; left for the caller's repair step
+        args => args.to_string(),
+    };
+    if response.finish_reason == "tool_call" && response.tool_calls.is_empty() {
+        response.finish_reason = "stop".to_string();
+    }
+}
+
+fn stop_reason_str(stop_reason: &str) -> &'static str {
+    match stop_reason {
+        "max_tokens" => "length",
+        "tool_use" => "tool_call",
+        "refusal" => "content_filter",
+        _ => "stop", // end_turn, stop_sequence, pause_turn
+    }
 }
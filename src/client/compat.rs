@@ -0,0 +1,110 @@
+//! Profiles for backends that speak the OpenAI Chat Completions API with small quirks.
+
+use serde::{Deserialize, Serialize};
+
+/// What a Chat Completions backend accepts; `build_openai_request` leaves out anything unsupported.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatCapabilities {
+    pub response_format: bool,       // `response_format: json_object`
+    #[serde(default)]
+    pub json_schema: bool,           // `response_format: json_schema` (structured outputs)
+    pub stream_usage: bool,          // `stream_options.include_usage`
+    pub tools: bool,                 // `tools` / `tool_choice`
+    pub max_completion_tokens: bool, // `max_completion_tokens` instead of the legacy `max_tokens`
+}
+
+impl CompatCapabilities {
+    /// Everything the OpenAI API itself accepts.
+    pub const fn all() -> Self {
+        Self {
+            response_format: true,
+            json_schema: true,
+            stream_usage: true,
+            tools: true,
+            max_completion_tokens: true,
+        }
+    }
+}
+
+/// A known OpenAI-compatible backend, or `Custom` with explicit capabilities.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum CompatProfile {
+    Groq,
+    Together,
+    DeepSeek,
+    Vllm,
+    LmStudio,
+    LlamaCpp,
+    Custom(CompatCapabilities), // any other server; set `AskConfig.url`
+}
+
+impl CompatProfile {
+    pub fn capabilities(&self) -> CompatCapabilities {
+        match self {
+            CompatProfile::Groq => CompatCapabilities {
+                response_format: true,
+                json_schema: true,
+                stream_usage: false,
+                tools: true,
+                max_completion_tokens: true,
+            },
+            CompatProfile::Together => CompatCapabilities {
+                response_format: true,
+                json_schema: true,
+                stream_usage: false,
+                tools: true,
+                max_completion_tokens: false,
+            },
+            CompatProfile::DeepSeek => CompatCapabilities {
+                response_format: true,
+                json_schema: false,
+                stream_usage: true,
+                tools: true,
+                max_completion_tokens: false,
+            },
+            CompatProfile::Vllm => CompatCapabilities::all(),
+            CompatProfile::LmStudio => CompatCapabilities {
+                response_format: false,
+                json_schema: false,
+                stream_usage: false,
+                tools: true,
+                max_completion_tokens: false,
+            },
+            CompatProfile::LlamaCpp => CompatCapabilities {
+                response_format: true,
+                json_schema: true,
+                stream_usage: false,
+                tools: true,
+                max_completion_tokens: false,
+            },
+            CompatProfile::Custom(caps) => *caps,
+        }
+    }
+
+    pub fn default_url(&self) -> &'static str {
+        match self {
+            CompatProfile::Groq => "https://api.groq.com/openai/v1",
+            CompatProfile::Together => "https://api.together.xyz/v1",
+            CompatProfile::DeepSeek => "https://api.deepseek.com/v1",
+            CompatProfile::Vllm | CompatProfile::Custom(_) => "http://localhost:8000/v1",
+            CompatProfile::LmStudio => "http://localhost:1234/v1",
+            CompatProfile::LlamaCpp => "http://localhost:8080/v1",
+        }
+    }
+}
+
+impl std::fmt::Display for CompatProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompatProfile::Groq => write!(f, "Groq"),
+            CompatProfile::Together => write!(f, "Together"),
+            CompatProfile::DeepSeek => write!(f, "DeepSeek"),
+            CompatProfile::Vllm => write!(f, "vLLM"),
+            CompatProfile::LmStudio => write!(f, "LMStudio"),
+            CompatProfile::LlamaCpp => write!(f, "LlamaCpp"),
+            CompatProfile::Custom(_) => write!(f, "OpenAI-Compatible"),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Per-model pricing, the dollar cost of a call, and a running total across calls.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::ask::request::AskRequest;
+use crate::ask::response::{AskResponse, Cost, Usage};
+use crate::client::universal::Middleware;
+use crate::client::ProviderAPI;
+
+/// List price of one model in USD per million tokens.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    #[serde(default)]
+    pub cached_input_per_mtok: Option<f64>, // cache reads; billed as regular input when unset
+    #[serde(default)]
+    pub cache_write_per_mtok: Option<f64>,  // cache writes; billed as regular input when unset
+    #[serde(default)]
+    pub cache_write_1h_per_mtok: Option<f64>, // 1-hour cache writes; billed as other cache writes when unset
+}
+
+impl ModelPrice {
+    pub fn new(input_per_mtok: f64, output_per_mtok: f64, cached_input_per_mtok: Option<f64>) -> Self {
+        Self {
+            input_per_mtok,
+            output_per_mtok,
+            cached_input_per_mtok,
+            cache_write_per_mtok: None,
+            cache_write_1h_per_mtok: None,
+        }
+    }
+
+    pub fn with_cache_write(mut self, cache_write_per_mtok: f64) -> Self {
+        self.cache_write_per_mtok = Some(cache_write_per_mtok);
+        self
+    }
+
+    pub fn with_cache_write_1h(mut self, cache_write_1h_per_mtok: f64) -> Self {
+        self.cache_write_1h_per_mtok = Some(cache_write_1h_per_mtok);
+        self
+    }
+
+    pub fn cost(&self, usage: &Usage) -> Cost {
+        let cached = usage.cached_tokens.unwrap_or(0);
+        let written = usage.cache_write_tokens.unwrap_or(0);
+        let written_1h = usage.cache_write_1h_tokens.unwrap_or(0).min(written);
+        let uncached = usage.prompt_tokens.unwrap_or(0).saturating_sub(cached + written);
+        let per_token = |tokens: u32, per_mtok: f64| tokens as f64 * per_mtok / 1e6;
+
+        let input = per_token(uncached, self.input_per_mtok);
+        let cached_input = per_token(cached, self.cached_input_per_mtok.unwrap_or(self.input_per_mtok));
+        let write_per_mtok = self.cache_write_per_mtok.unwrap_or(self.input_per_mtok);
+        let cache_write = per_token(written - written_1h, write_per_mtok)
+            + per_token(written_1h, self.cache_write_1h_per_mtok.unwrap_or(write_per_mtok));
+        let output = per_token(usage.completion_tokens.unwrap_or(0), self.output_per_mtok);
+        Cost {
+            input,
+            output,
+            cached_input,
+            cache_write,
+            total: input + cached_input + cache_write + output,
+        }
+    }
+}
+
+/// Prices keyed by provider and model. A model matches its exact id, or its id without a dated
+/// snapshot suffix, so `claude-sonnet-4-5-20250929` and `gpt-4o-2024-08-06` pick up
+/// `claude-sonnet-4-5` and `gpt-4o`. Any other id (`o3-pro`, `gpt-4o-audio-preview`) is unpriced.
+#[derive(Clone, Default)]
+pub struct PricingTable {
+    prices: Arc<RwLock<HashMap<(String, String), ModelPrice>>>,
+}
+
+static GLOBAL_PRICING: LazyLock<PricingTable> = LazyLock::new(PricingTable::with_defaults);
+
+impl PricingTable {
+    /// An empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide table every call is priced against, seeded with published list prices.
+    /// Override entries at runtime when prices change or you have negotiated rates.
+    pub fn global() -> &'static PricingTable {
+        &GLOBAL_PRICING
+    }
+
+    /// Set (or replace) the price of `model` on `api`.
+    pub fn set(&self, api: &ProviderAPI, model: impl Into<String>, price: ModelPrice) {
+        let mut prices = self.prices.write().unwrap_or_else(|e| e.into_inner());
+        prices.insert((provider_key(api), model.into()), price);
+    }
+
+    pub fn remove(&self, api: &ProviderAPI, model: &str) -> Option<ModelPrice> {
+        let mut prices = self.prices.write().unwrap_or_else(|e| e.into_inner());
+        prices.remove(&(provider_key(api), model.to_string()))
+    }
+
+    pub fn get(&self, api: &ProviderAPI, model: &str) -> Option<ModelPrice> {
+        let provider = provider_key(api);
+        let prices = self.prices.read().unwrap_or_else(|e| e.into_inner());
+        let lookup = |model: &str| prices.get(&(provider.clone(), model.to_string())).copied();
+        lookup(model).or_else(|| undated(model).and_then(lookup))
+    }
+
+    /// Cost of `usage` on `model`, or `None` when the model is not priced.
+    pub fn cost(&self, api: &ProviderAPI, model: &str, usage: &Usage) -> Option<Cost> {
+        self.get(api, model).map(|price| price.cost(usage))
+    }
+
+    fn with_defaults() -> Self {
+        let table = Self::new();
+        let defaults = [
+            (ProviderAPI::OpenAI, "gpt-4o", 2.50, 10.00, 1.25),
+            (ProviderAPI::OpenAI, "gpt-4o-mini", 0.15, 0.60, 0.075),
+            (ProviderAPI::OpenAI, "gpt-4.1", 2.00, 8.00, 0.50),
+            (ProviderAPI::OpenAI, "gpt-4.1-mini", 0.40, 1.60, 0.10),
+            (ProviderAPI::OpenAI, "gpt-4.1-nano", 0.10, 0.40, 0.025),
+            (ProviderAPI::OpenAI, "gpt-5", 1.25, 10.00, 0.125),
+            (ProviderAPI::OpenAI, "gpt-5-mini", 0.25, 2.00, 0.025),
+            (ProviderAPI::OpenAI, "gpt-5-nano", 0.05, 0.40, 0.005),
+            (ProviderAPI::OpenAI, "o3", 2.00, 8.00, 0.50),
+            (ProviderAPI::OpenAI, "o3-mini", 1.10, 4.40, 0.55),
+            (ProviderAPI::OpenAI, "o4-mini", 1.10, 4.40, 0.275),
+            (ProviderAPI::Anthropic, "claude-opus-4", 15.00, 75.00, 1.50),
+            (ProviderAPI::Anthropic, "claude-opus-4-0", 15.00, 75.00, 1.50),
+            (ProviderAPI::Anthropic, "claude-opus-4-1", 15.00, 75.00, 1.50),
+            (ProviderAPI::Anthropic, "claude-opus-4-5", 5.00, 25.00, 0.50),
+            (ProviderAPI::Anthropic, "claude-sonnet-4", 3.00, 15.00, 0.30),
+            (ProviderAPI::Anthropic, "claude-sonnet-4-0", 3.00, 15.00, 0.30),
+            (ProviderAPI::Anthropic, "claude-sonnet-4-5", 3.00, 15.00, 0.30),
+            (ProviderAPI::Anthropic, "claude-haiku-4-5", 1.00, 5.00, 0.10),
+            (ProviderAPI::Anthropic, "claude-3-5-haiku", 0.80, 4.00, 0.08),
+            (ProviderAPI::Gemini, "gemini-2.5-pro", 1.25, 10.00, 0.31),
+            (ProviderAPI::Gemini, "gemini-2.5-flash", 0.30, 2.50, 0.075),
+            (ProviderAPI::Gemini, "gemini-2.5-flash-lite", 0.10, 0.40, 0.025),
+            (ProviderAPI::Gemini, "gemini-2.0-flash", 0.10, 0.40, 0.025),
+        ];
+        for (api, model, input, output, cached) in defaults {
+            let mut price = ModelPrice::new(input, output, Some(cached));
+            if api == ProviderAPI::Anthropic {
+                // 5-minute cache writes cost 1.25x input, 1-hour writes 2x
+                price = price.with_cache_write(input * 1.25).with_cache_write_1h(input * 2.0);
+            }
+            table.set(&api, model, price);
+        }
+        table
+    }
+}
+
+fn provider_key(api: &ProviderAPI) -> String {
+    api.to_string().to_lowercase()
+}
+
+/// `model` without a trailing snapshot date, `-YYYY-MM-DD` or `-YYYYMMDD`.
+fn undated(model: &str) -> Option<&str> {
+    ["-0000-00-00", "-00000000"].into_iter().find_map(|pattern| {
+        let (base, date) = model.split_at_checked(model.len().checked_sub(pattern.len())?)?;
+        let is_date = date
+            .bytes()
+            .zip(pattern.bytes())
+            .all(|(c, p)| if p == b'-' { c == b'-' } else { c.is_ascii_digit() });
+        (!base.is_empty() && is_date).then_some(base)
+    })
+}
+
+#[derive(Default)]
+struct CostTotals {
+    total: Cost,
+    by_ref: HashMap<String, Cost>,
+}
+
+/// Running cost of every call recorded into it. Cheap to clone; clones share the totals.
+///
+/// Add it to a `Client` as middleware to record every successful call, or call `record`
+/// yourself. Each call also counts toward every id in its `AskRequest.context_refs`.
+#[derive(Clone, Default)]
+pub struct CostTracker {
+    totals: Arc<Mutex<CostTotals>>,
+}
+
+impl CostTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `response.cost` (unpriced calls are skipped).
+    pub fn record(&self, request: &AskRequest, response: &AskResponse) {
+        let Some(cost) = &response.cost else { return };
+        let mut totals = self.totals.lock().unwrap_or_else(|e| e.into_inner());
+        totals.total += cost;
+        for id in &request.context_refs {
+            *totals.by_ref.entry(id.clone()).or_default() += cost;
+        }
+    }
+
+    pub fn total(&self) -> Cost {
+        self.totals.lock().unwrap_or_else(|e| e.into_inner()).total.clone()
+    }
+
+    /// Spend on calls that listed `id` in their `context_refs`.
+    pub fn for_ref(&self, id: &str) -> Cost {
+        let totals = self.totals.lock().unwrap_or_else(|e| e.into_inner());
+        totals.by_ref.get(id).cloned().unwrap_or_default()
+    }
+
+    pub fn reset(&self) {
+        *self.totals.lock().unwrap_or_else(|e| e.into_inner()) = CostTotals::default();
+    }
+}
+
+#[async_trait]
+impl Middleware for CostTracker {
+    async fn on_response(&self, request: &AskRequest, response: &mut AskResponse) {
+        self.record(request, response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> PricingTable {
+        let table = PricingTable::new();
+        table.set(&ProviderAPI::OpenAI, "o3", ModelPrice::new(2.0, 8.0, None));
+        table.set(&ProviderAPI::OpenAI, "gpt-4o", ModelPrice::new(2.5, 10.0, None));
+        table.set(&ProviderAPI::OpenAI, "gpt-4o-mini", ModelPrice::new(0.15, 0.6, None));
+        table.set(&ProviderAPI::Anthropic, "claude-sonnet-4-5", ModelPrice::new(3.0, 15.0, None));
+        table
+    }
+
+    #[test]
+    fn get_matches_exact_and_dated_ids() {
+        let table = table();
+        let input = |api, model| table.get(&api, model).map(|p| p.input_per_mtok);
+
+        assert_eq!(input(ProviderAPI::OpenAI, "gpt-4o"), Some(2.5));
+        assert_eq!(input(ProviderAPI::OpenAI, "gpt-4o-mini"), Some(0.15));
+        assert_eq!(input(ProviderAPI::OpenAI, "gpt-4o-2024-08-06"), Some(2.5));
+        assert_eq!(input(ProviderAPI::OpenAI, "gpt-4o-mini-2024-07-18"), Some(0.15));
+        assert_eq!(input(ProviderAPI::Anthropic, "claude-sonnet-4-5-20250929"), Some(3.0));
+    }
+
+    #[test]
+    fn get_rejects_other_suffixes_and_providers() {
+        let table = table();
+        assert!(table.get(&ProviderAPI::OpenAI, "o3-pro").is_none());
+        assert!(table.get(&ProviderAPI::OpenAI, "o3-mini").is_none());
+        assert!(table.get(&ProviderAPI::OpenAI, "gpt-4o-audio-preview").is_none());
+        assert!(table.get(&ProviderAPI::OpenAI, "gpt-4o-2024-08").is_none());
+        assert!(table.get(&ProviderAPI::Anthropic, "gpt-4o").is_none());
+    }
+}
@@ -0,0 +1,74 @@
+//! Ordered provider/model fallbacks for when the preferred backend is unavailable.
+
+use std::future::Future;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ask::config::AskConfig;
+use crate::ask::request::AskRequest;
+use crate::ask::response::AskResponse;
+use crate::client::provider::{AskStream, ProviderRegistry};
+use crate::client::retry::{ask_stream_with_retry, ask_with_retry};
+use crate::error::AiError;
+
+/// `AskConfig`s tried in order. A call moves on to the next entry when it fails with a
+/// retryable error (after that entry's own `retry` policy is used up); any other error is
+/// returned as is. `AskResponse.provider` / `model` tell which entry answered; `latency_ms`
+/// counts from the start of the first entry.
+///
+/// Entries pick their provider from `AskConfig.api`; `AskRequest.provider` is not consulted.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FallbackChain {
+    pub configs: Vec<AskConfig>,
+}
+
+impl FallbackChain {
+    pub fn new(configs: Vec<AskConfig>) -> Self {
+        Self { configs }
+    }
+
+    pub fn with_fallback(mut self, config: AskConfig) -> Self {
+        self.configs.push(config);
+        self
+    }
+
+    pub async fn ask(&self, request: &AskRequest) -> Result<AskResponse, AiError> {
+        let started = Instant::now();
+        self.try_each(|_, config| async move {
+            let provider = ProviderRegistry::global().resolve(None, &config.api)?;
+            ask_with_retry(&*provider, config, request, started).await
+        })
+        .await
+    }
+
+    /// Falls back until the first chunk arrives; after that the stream is final.
+    pub async fn ask_stream<'a>(&self, request: &'a AskRequest) -> Result<AskStream<'a>, AiError> {
+        let started = Instant::now();
+        self.try_each(|_, config| async move {
+            let provider = ProviderRegistry::global().resolve(None, &config.api)?;
+            ask_stream_with_retry(&*provider, config, request, started).await
+        })
+        .await
+    }
+
+    /// Run `call` with each entry (and its position) until one succeeds or fails for good.
+    pub(crate) async fn try_each<T, F, Fut>(&self, mut call: F) -> Result<T, AiError>
+    where
+        F: FnMut(usize, AskConfig) -> Fut,
+        Fut: Future<Output = Result<T, AiError>>,
+    {
+        let mut last_err = None;
+        for (index, config) in self.configs.iter().enumerate() {
+            match call(index, config.clone()).await {
+                Err(e) if e.is_retryable() => last_err = Some(e),
+                result => return result,
+            }
+        }
+        Err(last_err.unwrap_or_else(empty_chain))
+    }
+}
+
+fn empty_chain() -> AiError {
+    AiError::Provider("fallback chain has no configs".to_string())
+}
@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+
+use async_stream::try_stream;
+use futures_core::Stream;
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+
+use crate::{
+    ask::{
+        config::AskConfig,
+        msg::{ContentPart, MediaSource, Msg, MsgContent, Role},
+        request::{AskChunk, AskRequest},
+        response::{AskResponse, Usage},
+        tool::{ToolCall, ToolChoice},
+    },
+    client::{ratelimit, sse::sse_events},
+    error::{AiError, ErrorInfo},
+    util::{endpoint, error_info, get_http_client, map_reqwest_err},
+};
+
+pub struct GeminiApi;
+
+impl GeminiApi {
+    /// Non-streaming: `models/{model}:generateContent`.
+    pub async fn ask(
+        config: AskConfig,
+        request: &AskRequest,
+    ) -> Result<AskResponse, AiError> {
+        let path = format!("{}:generateContent", model_path(&config.model));
+        let resp = send(&config, &path, &build_gemini_body(request), Some(config.request_timeout)).await?;
+        let body = resp.bytes().await.map_err(map_reqwest_err)?;
+        let body: Value = serde_json::from_slice(&body).map_err(|e| AiError::Json(e.to_string()))?;
+
+        let mut resp = ResponseAssembler::default();
+        resp.apply(&body);
+        Ok(resp.finish(body))
+    }
+
+    /// Streaming: `models/{model}:streamGenerateContent?alt=sse`; every event is a partial response.
+    pub async fn ask_stream(
+        config: AskConfig,
+        request: &AskRequest,
+    ) -> Result<impl Stream<Item = Result<AskChunk, AiError>> + Send, AiError> {
+        let path = format!("{}:streamGenerateContent?alt=sse", model_path(&config.model));
+        let resp = send(&config, &path, &build_gemini_body(request), None).await?;
+        let mut events = Box::pin(sse_events(resp));
+
+        let s = try_stream! {
+            let mut assembler = ResponseAssembler::default();
+            let mut last = Value::Null;
+
+            while let Some(event) = events.next().await {
+                let event = event?;
+                if event.data.is_empty() {
+                    continue;
+                }
+                let chunk: Value = serde_json::from_str(&event.data).map_err(|e| AiError::Json(e.to_string()))?;
+                if chunk.get("error").is_some() {
+                    Err(gemini_error(ErrorInfo::default(), &chunk))?;
+                }
+
+                let first_call = assembler.tool_calls.len();
+                let text = assembler.apply(&chunk);
+                if !text.is_empty() {
+                    yield AskChunk::Delta { text };
+                }
+                // Gemini sends each function call whole, never split across chunks
+                for (i, tc) in assembler.tool_calls.iter().enumerate().skip(first_call) {
+                    yield AskChunk::ToolCallDelta {
+                        index: i as u32,
+                        tool_call_id: tc.id.clone(),
+                        name: Some(tc.name.clone()),
+                        args_delta: Some(tc.arguments.to_string()),
+                    };
+                }
+                last = chunk;
+            }
+
+            yield AskChunk::Complete(assembler.finish(last));
+        };
+
+        Ok(s)
+    }
+
+    pub async fn get_models(config: &AskConfig) -> Result<Value, AiError> {
+        let http = get_http_client(config)?;
+        let resp = http
+            .get(endpoint(config, "models?pageSize=1000"))
+            .header("x-goog-api-key", &config.api_key)
+            .timeout(config.request_timeout)
+            .send()
+            .await
+            .map_err(map_reqwest_err)?;
+
+        if !resp.status().is_success() {
+            let info = error_info(&resp);
+            let error: Value = resp.json().await.unwrap_or(Value::Null);
+            return Err(gemini_error(info, &error));
+        }
+        let body: Value = resp.json().await.map_err(|e| AiError::Json(e.to_string()))?;
+
+        let models = body["models"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m["name"].as_str())
+            .map(|name| name.trim_start_matches("models/").to_string())
+            .collect::<Vec<String>>();
+        Ok(json!(models))
+    }
+
+    pub async fn get_embedding(
+        text: &str,
+        config: &AskConfig,
+        model: Option<&str>,
+    ) -> Result<Vec<f32>, AiError> {
+        let model = model.unwrap_or("text-embedding-004");
+        let path = format!("{}:embedContent", model_path(model));
+        let body = json!({ "content": { "parts": [{ "text": text }] } });
+
+        let resp = send(config, &path, &body, Some(config.request_timeout)).await?;
+        let body: Value = resp.json().await.map_err(|e| AiError::Json(e.to_string()))?;
+
+        let embedding = body["embedding"]["values"]
+            .as_array()
+            .map(|vals| vals.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect())
+            .unwrap_or_default();
+        Ok(embedding)
+    }
+}
+
+/// `models/{model}`, accepting ids with or without the `models/` prefix.
+fn model_path(model: &str) -> String {
+    format!("models/{}", model.trim_start_matches("models/"))
+}
+
+async fn send(config: &AskConfig, path: &str, body: &Value, timeout: Option<std::time::Duration>) -> Result<reqwest::Response, AiError> {
+    let http = get_http_client(config)?;
+    let mut builder = http
+        .post(endpoint(config, path))
+        .header("x-goog-api-key", &config.api_key)
+        .json(body);
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+
+    let resp = builder.send().await.map_err(map_reqwest_err)?;
+    ratelimit::observe_headers(config, resp.headers());
+    if !resp.status().is_success() {
+        let info = error_info(&resp);
+        let body: Value = resp.json().await.unwrap_or(Value::Null);
+        return Err(gemini_error(info, &body));
+    }
+    Ok(resp)
+}
+
+/// Map a Google API error payload (`{"error":{"code","message","status"}}`) to `AiError`.
+fn gemini_error(mut info: ErrorInfo, body: &Value) -> AiError {
+    if info.status.is_none() {
+        info.status = body["error"]["code"].as_u64().map(|c| c as u16);
+    }
+    info.error_type = body["error"]["status"].as_str().map(str::to_string);
+    info.message = body["error"]["message"].as_str().unwrap_or("unknown error").to_string();
+    info.into_error()
+}
+
+/// User content as Gemini parts: inline base64 data or `fileData` for remote files.
+fn gemini_parts(content: &MsgContent) -> Vec<Value> {
+    let MsgContent::Parts(parts) = content else {
+        return vec![json!({ "text": content.text() })];
+    };
+    let media = |source: &MediaSource| match source {
+        MediaSource::Base64 { media_type, data } => json!({ "inlineData": { "mimeType": media_type, "data": data } }),
+        MediaSource::Url { url, media_type } => {
+            let mut file = json!({ "fileUri": url });
+            if let Some(media_type) = media_type {
+                file["mimeType"] = json!(media_type);
+            }
+            json!({ "fileData": file })
+        }
+    };
+    parts
+        .iter()
+        .map(|part| match part {
+            ContentPart::Text { text } => json!({ "text": text }),
+            ContentPart::Image { source } | ContentPart::Document { source, .. } => media(source),
+            ContentPart::Audio { data, format } => json!({ "inlineData": { "mimeType": format!("audio/{format}"), "data": data } }),
+        })
+        .collect()
+}
+
+fn build_gemini_body(request: &AskRequest) -> Value {
+    let mut system_parts: Vec<Value> = Vec::new();
+    if let Some(sys) = &request.system {
+        system_parts.push(json!({ "text": sys }));
+    }
+
+    // functionResponse is keyed by function name, so remember which call id used which tool
+    let mut call_names: HashMap<&str, &str> = HashMap::new();
+    let mut contents: Vec<Value> = Vec::with_capacity(request.messages.len());
+
+    for m in &request.messages {
+        match m {
+            Msg { role: Role::System, content, .. } => system_parts.push(json!({ "text": content.text() })),
+            Msg { role: Role::User, content, .. } => {
+                contents.push(json!({ "role": "user", "parts": gemini_parts(content) }));
+            }
+            Msg { role: Role::Assistant, content, tool_calls, .. } => {
+                let mut parts = Vec::with_capacity(tool_calls.len() + 1);
+                if !content.is_empty() || tool_calls.is_empty() {
+                    parts.push(json!({ "text": content.text() }));
+                }
+                for tc in tool_calls {
+                    call_names.insert(tc.id.as_str(), tc.name.as_str());
+                    parts.push(json!({ "functionCall": { "name": tc.name, "args": tc.arguments } }));
+                }
+                contents.push(json!({ "role": "model", "parts": parts }));
+            }
+            Msg { role: Role::Tool, content, name, .. } => {
+                let id = m.tool_call_id();
+                let fn_name = call_names
+                    .get(id.as_str())
+                    .map(|n| n.to_string())
+                    .or_else(|| name.clone())
+                    .unwrap_or(id);
+                let content = content.text();
+                let response = serde_json::from_str::<Value>(&content)
+                    .ok()
+                    .filter(Value::is_object)
+                    .unwrap_or_else(|| json!({ "content": content }));
+                let part = json!({ "functionResponse": { "name": fn_name, "response": response } });
+
+                // Parallel function responses share one user turn
+                match contents.last_mut() {
+                    Some(last) if last["role"] == "user" && last["parts"][0].get("functionResponse").is_some() => {
+                        if let Some(parts) = last["parts"].as_array_mut() {
+                            parts.push(part);
+                        }
+                    }
+                    _ => contents.push(json!({ "role": "user", "parts": [part] })),
+                }
+            }
+        }
+    }
+
+    let mut body = json!({ "contents": contents });
+    if !system_parts.is_empty() {
+        body["systemInstruction"] = json!({ "parts": system_parts });
+    }
+
+    let mut generation = json!({});
+    if let Some(t) = request.options.temperature {
+        generation["temperature"] = json!(t);
+    }
+    if let Some(mx) = request.options.max_output_tokens {
+        generation["maxOutputTokens"] = json!(mx);
+    }
+    if let Some(spec) = &request.options.json_schema {
+        generation["responseMimeType"] = json!("application/json");
+        generation["responseJsonSchema"] = spec.schema.clone();
+    } else if request.options.json_mode.unwrap_or(false) {
+        generation["responseMimeType"] = json!("application/json");
+    }
+    if generation.as_object().is_some_and(|g| !g.is_empty()) {
+        body["generationConfig"] = generation;
+    }
+
+    if !request.tools.is_empty() {
+        let decls: Vec<Value> = request
+            .tools
+            .iter()
+            .map(|t| {
+                let mut decl = json!({ "name": t.name, "parameters": t.parameters });
+                if let Some(desc) = &t.description {
+                    decl["description"] = json!(desc);
+                }
+                decl
+            })
+            .collect();
+        body["tools"] = json!([{ "functionDeclarations": decls }]);
+
+        if let Some(choice) = &request.tool_choice {
+            body["toolConfig"] = json!({ "functionCallingConfig": match choice {
+                ToolChoice::Auto => json!({ "mode": "AUTO" }),
+                ToolChoice::None => json!({ "mode": "NONE" }),
+                ToolChoice::Required => json!({ "mode": "ANY" }),
+                ToolChoice::Tool(name) => json!({ "mode": "ANY", "allowedFunctionNames": [name] }),
+            }});
+        }
+    }
+    body
+}
+
+/// Folds one or more `GenerateContentResponse` objects into a single `AskResponse`.
+#[derive(Default)]
+struct ResponseAssembler {
+    text: String,
+    tool_calls: Vec<ToolCall>,
+    finish_reason: Option<String>,
+    usage: Option<Usage>,
+}
+
+impl ResponseAssembler {
+    /// Absorb one response (or stream chunk); returns the visible text it added.
+    fn apply(&mut self, body: &Value) -> String {
+        let mut added = String::new();
+        let candidate = &body["candidates"][0];
+
+        for part in candidate["content"]["parts"].as_array().into_iter().flatten() {
+            if part["thought"].as_bool().unwrap_or(false) {
+                continue;
+            }
+            if let Some(t) = part["text"].as_str() {
+                added.push_str(t);
+            }
+            if let Some(fc) = part.get("functionCall") {
+                let name = fc["name"].as_str().unwrap_or_default().to_string();
+                let id = fc["id"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("{}-{}", name, self.tool_calls.len()));
+                self.tool_calls.push(ToolCall { id, name, arguments: fc["args"].clone() });
+            }
+        }
+        self.text.push_str(&added);
+
+        if let Some(fr) = candidate["finishReason"].as_str() {
+            self.finish_reason = Some(finish_reason_str(fr).to_string());
+        } else if body["promptFeedback"]["blockReason"].is_string() {
+            // The prompt itself was blocked: no candidates at all
+            self.finish_reason = Some("content_filter".to_string());
+        }
+
+        if let Some(u) = body.get("usageMetadata") {
+            let count = |k: &str| u[k].as_u64().map(|n| n as u32);
+            self.usage = Some(Usage {
+                prompt_tokens: count("promptTokenCount"),
+                // Thoughts are billed as output but reported apart from the candidates
+                completion_tokens: match (count("candidatesTokenCount"), count("thoughtsTokenCount")) {
+                    (Some(candidates), thoughts) => Some(candidates + thoughts.unwrap_or(0)),
+                    (None, thoughts) => thoughts,
+                },
+                total_tokens: count("totalTokenCount"),
+                cached_tokens: count("cachedContentTokenCount"),
+                reasoning_tokens: count("thoughtsTokenCount"),
+                ..Default::default()
+            });
+        }
+        added
+    }
+
+    fn finish(self, provider_meta: Value) -> AskResponse {
+        let finish_reason = match self.finish_reason.as_deref() {
+            Some("stop") | None if !self.tool_calls.is_empty() => "tool_call".to_string(),
+            Some(fr) => fr.to_string(),
+            None => "stop".to_string(),
+        };
+        AskResponse {
+            text: self.text,
+            finish_reason,
+            usage: self.usage,
+            latency_ms: 0,
+            model: provider_meta["modelVersion"].as_str().unwrap_or_default().to_string(),
+            provider_meta,
+            tool_calls: self.tool_calls,
+            ..Default::default()
+        }
+    }
+}
+
+fn finish_reason_str(fr: &str) -> &'static str {
+    match fr {
+        "STOP" => "stop",
+        "MAX_TOKENS" => "length",
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" | "IMAGE_SAFETY" => "content_filter",
+        "MALFORMED_FUNCTION_CALL" | "UNEXPECTED_TOOL_CALL" => "error",
+        _ => "stop", // LANGUAGE, OTHER, FINISH_REASON_UNSPECIFIED
+    }
+}
@@ -0,0 +1,370 @@
+use std::collections::HashMap;
+
+use async_stream::try_stream;
+use futures_core::Stream;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    ask::{
+        config::AskConfig,
+        msg::{ContentPart, MediaSource, Msg, MsgContent, Role},
+        request::{AskChunk, AskRequest},
+        response::{AskResponse, Usage},
+        tool::ToolCall,
+    },
+    client::ratelimit,
+    error::{AiError, ErrorInfo},
+    util::{endpoint, error_info, get_http_client, map_reqwest_err, unsupported_part},
+};
+
+/// Ollama-only runtime options (see the Ollama `Modelfile` parameter docs).
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OllamaOptions {
+    #[serde(default)]
+    pub num_ctx: Option<u32>,       // context window; Ollama's default is small
+    #[serde(default)]
+    pub keep_alive: Option<String>, // how long the model stays loaded, e.g. "5m", "0", "-1"
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub top_k: Option<u32>,
+    #[serde(default)]
+    pub top_p: Option<f32>,
+}
+
+pub struct OllamaApi;
+
+impl OllamaApi {
+    /// Non-streaming: one `/api/chat` call with `stream: false`.
+    pub async fn ask(
+        config: AskConfig,
+        request: &AskRequest,
+    ) -> Result<AskResponse, AiError> {
+        let body = build_ollama_body(&config, request, false)?;
+        let resp = send(&config, "api/chat", &body, Some(config.request_timeout)).await?;
+        let bytes = resp.bytes().await.map_err(map_reqwest_err)?;
+        let line: Value = serde_json::from_slice(&bytes).map_err(|e| AiError::Json(e.to_string()))?;
+
+        let mut assembler = ResponseAssembler::default();
+        assembler.apply(&line);
+        Ok(assembler.finish(line))
+    }
+
+    /// Streaming: `/api/chat` answers with one JSON object per line until `done: true`; a body
+    /// that ends before that line is an error.
+    pub async fn ask_stream(
+        config: AskConfig,
+        request: &AskRequest,
+    ) -> Result<impl Stream<Item = Result<AskChunk, AiError>> + Send, AiError> {
+        let body = build_ollama_body(&config, request, true)?;
+        let resp = send(&config, "api/chat", &body, None).await?;
+        let mut lines = Box::pin(ndjson_lines(resp));
+
+        let s = try_stream! {
+            let mut assembler = ResponseAssembler::default();
+            let mut last = Value::Null;
+            let mut done = false;
+
+            while let Some(line) = lines.next().await {
+                let line = line?;
+                if let Some(err) = line["error"].as_str() {
+                    Err(AiError::Provider(err.to_string()))?;
+                }
+
+                let first_call = assembler.tool_calls.len();
+                let text = assembler.apply(&line);
+                if !text.is_empty() {
+                    yield AskChunk::Delta { text };
+                }
+                for (i, tc) in assembler.tool_calls.iter().enumerate().skip(first_call) {
+                    yield AskChunk::ToolCallDelta {
+                        index: i as u32,
+                        tool_call_id: tc.id.clone(),
+                        name: Some(tc.name.clone()),
+                        args_delta: Some(tc.arguments.to_string()),
+                    };
+                }
+
+                done = line["done"].as_bool().unwrap_or(false);
+                last = line;
+                if done {
+                    break;
+                }
+            }
+
+            // The final line carries the counters and stop reason; without it the answer is partial
+            if !done {
+                Err(AiError::Http("stream ended before the done line".to_string()))?;
+            }
+            yield AskChunk::Complete(assembler.finish(last));
+        };
+
+        Ok(s)
+    }
+
+    /// Locally installed models from `/api/tags`.
+    pub async fn get_models(config: &AskConfig) -> Result<Value, AiError> {
+        let http = get_http_client(config)?;
+        let mut builder = http.get(endpoint(config, "api/tags")).timeout(config.request_timeout);
+        if !config.api_key.is_empty() {
+            builder = builder.bearer_auth(&config.api_key);
+        }
+        let resp = builder.send().await.map_err(map_reqwest_err)?;
+
+        if !resp.status().is_success() {
+            let info = error_info(&resp);
+            let error: Value = resp.json().await.unwrap_or(Value::Null);
+            return Err(ollama_error(info, &error, None));
+        }
+        let body: Value = resp.json().await.map_err(|e| AiError::Json(e.to_string()))?;
+
+        let models = body["models"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m["name"].as_str().map(str::to_string))
+            .collect::<Vec<String>>();
+        Ok(json!(models))
+    }
+
+    pub async fn get_embedding(
+        text: &str,
+        config: &AskConfig,
+        model: Option<&str>,
+    ) -> Result<Vec<f32>, AiError> {
+        let model = model.unwrap_or("nomic-embed-text");
+        let body = json!({ "model": model, "input": text });
+
+        let resp = send(config, "api/embed", &body, Some(config.request_timeout)).await?;
+        let body: Value = resp.json().await.map_err(|e| AiError::Json(e.to_string()))?;
+
+        let embedding = body["embeddings"][0]
+            .as_array()
+            .map(|vals| vals.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect())
+            .unwrap_or_default();
+        Ok(embedding)
+    }
+}
+
+async fn send(config: &AskConfig, path: &str, body: &Value, timeout: Option<std::time::Duration>) -> Result<reqwest::Response, AiError> {
+    let http = get_http_client(config)?;
+    let mut builder = http.post(endpoint(config, path)).json(body);
+    // Plain Ollama needs no key; one is only sent for authenticated proxies
+    if !config.api_key.is_empty() {
+        builder = builder.bearer_auth(&config.api_key);
+    }
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+
+    let resp = builder.send().await.map_err(map_reqwest_err)?;
+    ratelimit::observe_headers(config, resp.headers());
+    if !resp.status().is_success() {
+        let info = error_info(&resp);
+        let error: Value = resp.json().await.unwrap_or(Value::Null);
+        // chat and embed name their model in the body; a 404 there means it is not pulled
+        return Err(ollama_error(info, &error, body["model"].as_str()));
+    }
+    Ok(resp)
+}
+
+/// Ollama errors are `{"error": "..."}`. A 404 on a call for `model` means that model is not
+/// pulled; elsewhere it means a wrong base url.
+fn ollama_error(mut info: ErrorInfo, body: &Value, model: Option<&str>) -> AiError {
+    if info.status == Some(404)
+        && let Some(model) = model
+    {
+        return AiError::UnknownModel(model.to_string());
+    }
+    info.message = body["error"].as_str().unwrap_or("unknown error").to_string();
+    info.into_error()
+}
+
+/// Splits a newline-delimited JSON body into values as chunks arrive.
+fn ndjson_lines(mut resp: reqwest::Response) -> impl Stream<Item = Result<Value, AiError>> + Send {
+    try_stream! {
+        let mut buf: Vec<u8> = Vec::new();
+
+        while let Some(bytes) = resp.chunk().await.map_err(map_reqwest_err)? {
+            buf.extend_from_slice(&bytes);
+            while let Some(end) = buf.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buf.drain(..=end).collect();
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                yield serde_json::from_slice::<Value>(&line).map_err(|e| AiError::Json(e.to_string()))?;
+            }
+        }
+
+        if !buf.iter().all(u8::is_ascii_whitespace) {
+            yield serde_json::from_slice::<Value>(&buf).map_err(|e| AiError::Json(e.to_string()))?;
+        }
+    }
+}
+
+/// Ollama takes images as bare base64 next to the text; remote urls, documents and audio are
+/// not supported.
+fn ollama_images(content: &MsgContent) -> Result<Vec<&str>, AiError> {
+    let MsgContent::Parts(parts) = content else { return Ok(Vec::new()) };
+    let mut images = Vec::new();
+    for part in parts {
+        match part {
+            ContentPart::Text { .. } => {}
+            ContentPart::Image { source: MediaSource::Base64 { data, .. } } => images.push(data.as_str()),
+            ContentPart::Image { source: MediaSource::Url { .. } } => return Err(unsupported_part("Ollama", "image url")),
+            ContentPart::Document { .. } => return Err(unsupported_part("Ollama", "document")),
+            ContentPart::Audio { .. } => return Err(unsupported_part("Ollama", "audio")),
+        }
+    }
+    Ok(images)
+}
+
+fn build_ollama_body(config: &AskConfig, request: &AskRequest, stream: bool) -> Result<Value, AiError> {
+    let mut messages: Vec<Value> = Vec::with_capacity(request.messages.len() + 1);
+    if let Some(sys) = &request.system {
+        messages.push(json!({ "role": "system", "content": sys }));
+    }
+
+    // Tool results are matched by tool name, so remember which call id used which tool
+    let mut call_names: HashMap<&str, &str> = HashMap::new();
+    for m in &request.messages {
+        match m {
+            Msg { role: Role::System, content, .. } => messages.push(json!({ "role": "system", "content": content.text() })),
+            Msg { role: Role::User, content, .. } => {
+                let mut msg = json!({ "role": "user", "content": content.text() });
+                let images = ollama_images(content)?;
+                if !images.is_empty() {
+                    msg["images"] = json!(images);
+                }
+                messages.push(msg);
+            }
+            Msg { role: Role::Assistant, content, tool_calls, .. } => {
+                let mut msg = json!({ "role": "assistant", "content": content.text() });
+                if !tool_calls.is_empty() {
+                    for tc in tool_calls {
+                        call_names.insert(tc.id.as_str(), tc.name.as_str());
+                    }
+                    msg["tool_calls"] = tool_calls
+                        .iter()
+                        .map(|tc| json!({ "function": { "name": tc.name, "arguments": tc.arguments } }))
+                        .collect();
+                }
+                messages.push(msg);
+            }
+            Msg { role: Role::Tool, content, .. } => {
+                let mut msg = json!({ "role": "tool", "content": content.text() });
+                if let Some(name) = call_names.get(m.tool_call_id().as_str()) {
+                    msg["tool_name"] = json!(name);
+                }
+                messages.push(msg);
+            }
+        }
+    }
+
+    let mut body = json!({
+        "model": config.model,
+        "messages": messages,
+        "stream": stream,
+    });
+
+    let mut options = json!({});
+    if let Some(t) = request.options.temperature {
+        options["temperature"] = json!(t);
+    }
+    if let Some(mx) = request.options.max_output_tokens {
+        options["num_predict"] = json!(mx);
+    }
+    if let Some(ollama) = &request.options.ollama {
+        if let Some(n) = ollama.num_ctx {
+            options["num_ctx"] = json!(n);
+        }
+        if let Some(seed) = ollama.seed {
+            options["seed"] = json!(seed);
+        }
+        if let Some(k) = ollama.top_k {
+            options["top_k"] = json!(k);
+        }
+        if let Some(p) = ollama.top_p {
+            options["top_p"] = json!(p);
+        }
+        if let Some(keep_alive) = &ollama.keep_alive {
+            body["keep_alive"] = json!(keep_alive);
+        }
+    }
+    if options.as_object().is_some_and(|o| !o.is_empty()) {
+        body["options"] = options;
+    }
+
+    // `format` takes either "json" or a JSON Schema the output is constrained to
+    if let Some(spec) = &request.options.json_schema {
+        body["format"] = spec.schema.clone();
+    } else if request.options.json_mode.unwrap_or(false) {
+        body["format"] = json!("json");
+    }
+    if !request.tools.is_empty() {
+        body["tools"] = request
+            .tools
+            .iter()
+            .map(|t| json!({
+                "type": "function",
+                "function": { "name": t.name, "description": t.description, "parameters": t.parameters },
+            }))
+            .collect();
+    }
+    Ok(body)
+}
+
+/// Folds one `/api/chat` response (or every streamed line) into a single `AskResponse`.
+#[derive(Default)]
+struct ResponseAssembler {
+    text: String,
+    tool_calls: Vec<ToolCall>,
+}
+
+impl ResponseAssembler {
+    /// Absorb one line; returns the text it added.
+    fn apply(&mut self, line: &Value) -> String {
+        let added = line["message"]["content"].as_str().unwrap_or_default().to_string();
+        self.text.push_str(&added);
+
+        for tc in line["message"]["tool_calls"].as_array().into_iter().flatten() {
+            let name = tc["function"]["name"].as_str().unwrap_or_default().to_string();
+            let id = format!("{}-{}", name, self.tool_calls.len());
+            self.tool_calls.push(ToolCall { id, name, arguments: tc["function"]["arguments"].clone() });
+        }
+        added
+    }
+
+    /// `last` is the final (`done: true`) line, which carries the stats.
+    fn finish(self, last: Value) -> AskResponse {
+        let finish_reason = match last["done_reason"].as_str() {
+            _ if !self.tool_calls.is_empty() => "tool_call",
+            Some("length") => "length",
+            _ => "stop",
+        }
+        .to_string();
+
+        let prompt = last["prompt_eval_count"].as_u64().map(|n| n as u32);
+        let completion = last["eval_count"].as_u64().map(|n| n as u32);
+        let usage = (prompt.is_some() || completion.is_some()).then(|| Usage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: Some(prompt.unwrap_or(0) + completion.unwrap_or(0)),
+            ..Default::default()
+        });
+
+        AskResponse {
+            text: self.text,
+            finish_reason,
+            usage,
+            latency_ms: 0,
+            model: last["model"].as_str().unwrap_or_default().to_string(),
+            provider_meta: last,
+            tool_calls: self.tool_calls,
+            ..Default::default()
+        }
+    }
+}
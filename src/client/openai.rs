@@ -1,145 +1,126 @@
-use async_openai::config::OpenAIConfig;
+use async_openai::config::{AzureConfig, Config, OpenAIConfig};
 use async_openai::types::{
+    ChatCompletionMessageToolCall,
+    ChatCompletionNamedToolChoice,
+    ChatCompletionStreamOptions,
     ChatCompletionRequestAssistantMessageArgs,
     ChatCompletionRequestMessage,
     ChatCompletionRequestSystemMessageArgs,
     ChatCompletionRequestToolMessageArgs,
     ChatCompletionRequestUserMessageArgs,
+    ChatCompletionTool,
+    ChatCompletionToolChoiceOption,
+    ChatCompletionToolType,
     CreateChatCompletionRequestArgs,
     CreateChatCompletionRequest, // <-- add this import
+    CreateChatCompletionResponse,
+    CreateChatCompletionStreamResponse,
+    CompletionUsage,
     FinishReason,
+    FunctionCall,
+    FunctionName,
+    FunctionObject,
+    ReasoningEffort as OaReasoningEffort,
     ResponseFormat,
+    ResponseFormatJsonSchema,
 };
 use async_openai::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use futures_util::StreamExt;
 use futures_core::Stream;
 use async_stream::try_stream;
+use serde_json::{json, Value};
+use reqwest::header::HeaderMap;
 
 use crate::ask::config::AskConfig;
-use crate::ask::msg::{Msg, Role};
-use crate::ask::request::{AskChunk, AskRequest};
+use crate::ask::msg::{ContentPart, MediaSource, Msg, MsgContent, Role};
+use crate::ask::request::{AskChunk, AskRequest, ReasoningEffort};
 use crate::ask::response::{AskResponse, Usage};
-use crate::error::AiError;
-use crate::util::get_http_client;
+use crate::ask::tool::{ToolCall, ToolCallAssembler, ToolChoice};
+use crate::client::ratelimit;
+use crate::client::responses::ResponsesApi;
+use crate::client::sse::sse_events;
+use crate::error::{AiError, ErrorInfo};
+use crate::util::{endpoint, error_info, get_http_client, map_reqwest_err, parse_tool_args, unsupported_part};
+
+/// Which OpenAI endpoint `OpenAiApi` sends chat requests to.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenAiBackend {
+    #[default]
+    ChatCompletions, // `/chat/completions`, also spoken by every OpenAI-compatible server
+    Responses,       // `/responses`: response chaining, reasoning summaries (OpenAI and Azure only)
+}
 
 pub struct OpenAiApi;
 
 impl OpenAiApi {
-    pub async fn get_client(config: &AskConfig) -> Result<Client<OpenAIConfig>, AiError> {
-        let http_client = get_http_client(&config)?;
+    /// async-openai client for model listing and embeddings; chat goes over `send_chat`.
+    pub async fn get_client(config: &AskConfig) -> Result<Client<Box<dyn Config>>, AiError> {
+        Self::get_client_for_model(config, &config.model).await
+    }
+
+    /// Azure routes by deployment, so the client depends on which model the call targets.
+    pub async fn get_client_for_model(config: &AskConfig, model: &str) -> Result<Client<Box<dyn Config>>, AiError> {
+        let http_client = get_http_client(config)?;
         let api_key = config.api_key.clone();
         let url = config.url.clone();
-        let oai_cfg = OpenAIConfig::new().with_api_base(url).with_api_key(api_key);
-        Ok(Client::with_config(oai_cfg).with_http_client(http_client))
+        let oai_cfg: Box<dyn Config> = match &config.azure {
+            Some(azure) => Box::new(
+                AzureConfig::new()
+                    .with_api_base(url)
+                    .with_api_key(api_key)
+                    .with_api_version(azure.api_version.clone())
+                    .with_deployment_id(azure.deployment_for(model)),
+            ),
+            None => Box::new(OpenAIConfig::new().with_api_base(url).with_api_key(api_key)),
+        };
+        let client = Client::with_config(oai_cfg).with_http_client(http_client);
+        // With our own policy in place, turn off async-openai's built-in rate-limit retries
+        Ok(match config.retry {
+            Some(_) => client.with_backoff(backoff::ExponentialBackoff {
+                max_elapsed_time: Some(Duration::ZERO),
+                ..Default::default()
+            }),
+            None => client,
+        })
     }
 
     pub async fn ask(
         config: AskConfig,
         request: &AskRequest,
     ) -> Result<AskResponse, AiError> {
-        let client = Self::get_client(&config).await?;
-        let req = build_openai_request(&config, request)?; // <-- build once here
-
-        let resp = client.chat().create(req).await.map_err(map_oai_err)?;
-
-        let text = resp.choices
-            .get(0)
-            .and_then(|c| c.message.content.clone())
-            .unwrap_or_default();
-
-        let finish_reason = resp.choices
-            .get(0)
-            .and_then(|c| c.finish_reason.as_ref())
-            .map(finish_reason_str)
-            .unwrap_or("stop")
-            .to_string();
-
-        let usage = resp.usage.as_ref().map(|u| Usage {
-            prompt_tokens: Some(u.prompt_tokens as u32),
-            completion_tokens: Some(u.completion_tokens as u32),
-            total_tokens: Some(u.total_tokens as u32),
-        });
+        if config.openai_backend == OpenAiBackend::Responses {
+            return ResponsesApi::ask(config, request).await;
+        }
+        let body = build_openai_body(&config, request, false)?;
+        let resp = send_chat(&config, &body, HeaderMap::new(), false).await?;
 
-        Ok(AskResponse {
-            text,
-            finish_reason,
-            usage,
-            latency_ms: 0,
-            provider_meta: serde_json::to_value(&resp).unwrap_or(serde_json::Value::Null),
-        })
+        Ok(parse_openai_response(&chat_response(resp).await?))
     }
 
     pub async fn ask_stream(
         config: AskConfig,
         request: &AskRequest,
     ) -> Result<impl Stream<Item = Result<AskChunk, AiError>> + Send, AiError> {
-        
-
-        let client = Self::get_client(&config).await?;
-        let req = build_openai_request(&config, request)?; // <-- reuse same builder logic
-
-        let mut stream = client.chat().create_stream(req).await.map_err(map_oai_err)?;
-
-        let mut full_text = String::new();
-        let mut finish_reason: Option<String> = None;
-        let mut provider_meta = serde_json::json!({});
-        let mut usage: Option<Usage> = None;
-
-        let s = try_stream! {
-            while let Some(event) = stream.next().await {
-                let chunk = event.map_err(map_oai_err)?;
-                provider_meta = serde_json::to_value(&chunk).unwrap_or(serde_json::Value::Null);
-
-                if let Some(choice) = chunk.choices.get(0) {
-                    let delta = &choice.delta;
-
-                    if let Some(ct) = &delta.content {
-                        full_text.push_str(ct);
-                        yield AskChunk::Delta { text: ct.clone() };
-                    }
-
-                    if let Some(tcs) = &delta.tool_calls {
-                        for tc in tcs {
-                            let id = tc.id.clone().unwrap_or_default();
-                            let name = tc.function.as_ref().and_then(|f| f.name.clone());
-                            let args_delta = tc.function.as_ref().and_then(|f| f.arguments.clone());
-                            yield AskChunk::ToolCallDelta { tool_call_id: id, name, args_delta };
-                        }
-                    }
-
-                    if let Some(role) = &delta.role {
-                        yield AskChunk::Role(role.to_string());
-                    }
-
-                    if let Some(fr) = &choice.finish_reason {
-                        finish_reason = Some(finish_reason_str(fr).to_string());
-                    }
-                }
-
-
-                if let Some(u) = &chunk.usage {
-                    usage = Some(Usage {
-                        prompt_tokens: Some(u.prompt_tokens as u32),
-                        completion_tokens: Some(u.completion_tokens as u32),
-                        total_tokens: Some(u.total_tokens as u32),
-                    });
-                }
-            }
-
-            let resp = AskResponse {
-                text: full_text,
-                finish_reason: finish_reason.unwrap_or_else(|| "stop".to_string()),
-                usage,
-                latency_ms: 0,
-                provider_meta,
-            };
-            yield AskChunk::Complete(resp);
-        };
+        if config.openai_backend == OpenAiBackend::Responses {
+            return Ok(ResponsesApi::ask_stream(config, request).await?.left_stream());
+        }
+        let body = build_openai_body(&config, request, true)?;
+        let resp = send_chat(&config, &body, HeaderMap::new(), true).await?;
 
-        Ok(s)
+        Ok(chat_chunk_stream(resp).right_stream())
     }
 
     pub async fn get_models(config: &AskConfig) -> Result<serde_json::Value, AiError> {
+        // Azure can only serve the models it has deployments for
+        if let Some(azure) = &config.azure {
+            let mut models = azure.deployments.keys().cloned().collect::<Vec<String>>();
+            models.sort();
+            return Ok(models.into());
+        }
+
         let client = Self::get_client(config).await?;
         let models = client.models().list().await.map_err(|e| AiError::Provider(e.to_string()))?.data;
         Ok(models.into_iter().map(|m| m.id).collect::<Vec<String>>().into())
@@ -150,8 +131,8 @@ impl OpenAiApi {
         config: &AskConfig,
         model: Option<&str>,
     ) -> Result<Vec<f32>, AiError> {
-        let client = Self::get_client(config).await?;
         let model = model.unwrap_or("text-embedding-ada-002");
+        let client = Self::get_client_for_model(config, model).await?;
 
         let req = async_openai::types::CreateEmbeddingRequestArgs::default()
             .model(model)
@@ -166,7 +147,7 @@ impl OpenAiApi {
             .map_err(map_oai_err)?;
 
         let embedding = resp.data
-            .get(0)
+            .first()
             .map(|e| e.embedding.clone())
             .unwrap_or_default();
 
@@ -174,6 +155,201 @@ impl OpenAiApi {
     }
 }
 
+/// POST a Chat Completions body to `{config.url}/chat/completions` (Azure: the deployment's
+/// endpoint) with any backend-specific `headers`, and fail fast on a non-success status.
+///
+/// Non-streaming calls are also bounded end-to-end by `request_timeout`; streams rely on the
+/// client's per-read timeout so long generations are not cut off.
+pub(crate) async fn send_chat(
+    config: &AskConfig,
+    body: &Value,
+    headers: HeaderMap,
+    stream: bool,
+) -> Result<reqwest::Response, AiError> {
+    let http = get_http_client(config)?;
+    let builder = match &config.azure {
+        Some(azure) => http
+            .post(endpoint(config, &format!("openai/deployments/{}/chat/completions", azure.deployment_for(&config.model))))
+            .query(&[("api-version", azure.api_version.as_str())])
+            .header("api-key", &config.api_key),
+        // Local OpenAI-compatible servers often run without a key
+        None if config.api_key.is_empty() => http.post(endpoint(config, "chat/completions")),
+        None => http.post(endpoint(config, "chat/completions")).bearer_auth(&config.api_key),
+    };
+    let mut builder = builder.headers(headers).json(body);
+    if !stream {
+        builder = builder.timeout(config.request_timeout);
+    }
+
+    let resp = builder.send().await.map_err(map_reqwest_err)?;
+    ratelimit::observe_headers(config, resp.headers());
+    if !resp.status().is_success() {
+        let info = error_info(&resp);
+        let body: Value = resp.json().await.unwrap_or(Value::Null);
+        return Err(openai_error(info, &body["error"]));
+    }
+    Ok(resp)
+}
+
+pub(crate) async fn chat_response(resp: reqwest::Response) -> Result<CreateChatCompletionResponse, AiError> {
+    let body = resp.bytes().await.map_err(map_reqwest_err)?;
+    serde_json::from_slice(&body).map_err(|e| AiError::Json(e.to_string()))
+}
+
+/// Map an OpenAI-style error object (`{"message","type","code"}`) to `AiError`.
+pub(crate) fn openai_error(mut info: ErrorInfo, error: &Value) -> AiError {
+    // OpenRouter puts the HTTP status in `code`; OpenAI puts a specific code there
+    let code = match &error["code"] {
+        Value::String(code) => Some(code.clone()),
+        Value::Number(code) => Some(code.to_string()),
+        _ => None,
+    };
+    let status = code.as_deref().and_then(|c| c.parse::<u16>().ok()).filter(|c| (400..600).contains(c));
+    if info.status.is_none() {
+        info.status = status;
+    }
+    info.error_type = code.filter(|_| status.is_none()).or(error["type"].as_str().map(str::to_string));
+    info.message = error["message"].as_str().unwrap_or("unknown error").to_string();
+    info.into_error()
+}
+
+/// Normalize a Chat Completions response (OpenAI or any backend speaking its wire format).
+pub(crate) fn parse_openai_response(resp: &CreateChatCompletionResponse) -> AskResponse {
+    let text = resp.choices
+        .first()
+        .and_then(|c| c.message.content.clone())
+        .unwrap_or_default();
+
+    let finish_reason = resp.choices
+        .first()
+        .and_then(|c| c.finish_reason.as_ref())
+        .map(finish_reason_str)
+        .unwrap_or("stop")
+        .to_string();
+
+    let tool_calls = resp.choices
+        .first()
+        .and_then(|c| c.message.tool_calls.as_ref())
+        .map(|tcs| tcs.iter().map(|tc| ToolCall {
+            id: tc.id.clone(),
+            name: tc.function.name.clone(),
+            arguments: parse_tool_args(&tc.function.arguments),
+        }).collect())
+        .unwrap_or_default();
+
+    let usage = resp.usage.as_ref().map(openai_usage);
+
+    AskResponse {
+        text,
+        finish_reason,
+        usage,
+        latency_ms: 0,
+        provider_meta: serde_json::to_value(resp).unwrap_or(serde_json::Value::Null),
+        response_id: Some(resp.id.clone()),
+        model: resp.model.clone(),
+        tool_calls,
+        ..Default::default()
+    }
+}
+
+/// Turn a Chat Completions SSE response into `AskChunk`s, ending with `AskChunk::Complete`, or
+/// with an error if the body ends before `[DONE]` or a finish reason.
+pub(crate) fn chat_chunk_stream(resp: reqwest::Response) -> impl Stream<Item = Result<AskChunk, AiError>> + Send {
+    let mut events = Box::pin(sse_events(resp));
+    let mut full_text = String::new();
+    let mut finish_reason: Option<String> = None;
+    let mut provider_meta = serde_json::json!({});
+    let mut usage: Option<Usage> = None;
+    let mut assembler = ToolCallAssembler::new();
+
+    try_stream! {
+        let mut done = false;
+        while let Some(event) = events.next().await {
+            let event = event?;
+            if event.data == "[DONE]" {
+                done = true;
+                break;
+            }
+            if event.data.is_empty() {
+                continue;
+            }
+            let data: Value = serde_json::from_str(&event.data).map_err(|e| AiError::Json(e.to_string()))?;
+            // Failures after the stream opened arrive as an error object (OpenRouter, Azure)
+            if data["error"].is_object() {
+                Err(openai_error(ErrorInfo::default(), &data["error"]))?;
+            }
+            let chunk: CreateChatCompletionStreamResponse =
+                serde_json::from_value(data).map_err(|e| AiError::Json(e.to_string()))?;
+            provider_meta = serde_json::to_value(&chunk).unwrap_or(serde_json::Value::Null);
+
+            if let Some(choice) = chunk.choices.first() {
+                let delta = &choice.delta;
+
+                // The first chunk often carries only the role, with empty content
+                if let Some(ct) = delta.content.as_ref().filter(|ct| !ct.is_empty()) {
+                    full_text.push_str(ct);
+                    yield AskChunk::Delta { text: ct.clone() };
+                }
+
+                if let Some(tcs) = &delta.tool_calls {
+                    for tc in tcs {
+                        let id = tc.id.clone().unwrap_or_default();
+                        let name = tc.function.as_ref().and_then(|f| f.name.clone());
+                        let args_delta = tc.function.as_ref().and_then(|f| f.arguments.clone());
+                        assembler.push(tc.index, &id, name.as_deref(), args_delta.as_deref());
+                        yield AskChunk::ToolCallDelta { index: tc.index, tool_call_id: id, name, args_delta };
+                    }
+                }
+
+                if let Some(role) = &delta.role {
+                    yield AskChunk::Role(role.to_string());
+                }
+
+                if let Some(fr) = &choice.finish_reason {
+                    finish_reason = Some(finish_reason_str(fr).to_string());
+                }
+            }
+
+
+            // Sent on a final chunk without choices when `stream_options.include_usage` is set
+            if let Some(u) = &chunk.usage {
+                usage = Some(openai_usage(u));
+            }
+        }
+
+        let finish_reason = match (finish_reason, done) {
+            (Some(fr), _) => fr,
+            (None, true) => "stop".to_string(),
+            // Neither `[DONE]` nor a finish reason: the body was cut off
+            (None, false) => Err(AiError::Http("stream ended before [DONE]".to_string()))?,
+        };
+        assembler.flush();
+        let resp = AskResponse {
+            text: full_text,
+            finish_reason,
+            usage,
+            latency_ms: 0,
+            model: provider_meta["model"].as_str().unwrap_or_default().to_string(),
+            provider_meta,
+            tool_calls: assembler.tool_calls().to_vec(),
+            ..Default::default()
+        };
+        yield AskChunk::Complete(resp);
+    }
+}
+
+fn openai_usage(u: &CompletionUsage) -> Usage {
+    Usage {
+        prompt_tokens: Some(u.prompt_tokens),
+        completion_tokens: Some(u.completion_tokens),
+        total_tokens: Some(u.total_tokens),
+        cached_tokens: u.prompt_tokens_details.as_ref().and_then(|d| d.cached_tokens),
+        cache_write_tokens: None, // caching is automatic; writes are not billed
+        cache_write_1h_tokens: None,
+        reasoning_tokens: u.completion_tokens_details.as_ref().and_then(|d| d.reasoning_tokens),
+    }
+}
+
 fn finish_reason_str(fr: &FinishReason) -> &'static str {
     match fr {
         FinishReason::Stop => "stop",
@@ -184,33 +360,81 @@ fn finish_reason_str(fr: &FinishReason) -> &'static str {
     }
 }
 
-fn map_oai_err(e: async_openai::error::OpenAIError) -> AiError {
+/// OpenAI expects tool arguments as a JSON-encoded string.
+pub(crate) fn tool_args_string(args: &serde_json::Value) -> String {
+    match args {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Errors from the async-openai client (model listing, embeddings).
+pub(crate) fn map_oai_err(e: async_openai::error::OpenAIError) -> AiError {
     use async_openai::error::OpenAIError as E;
     match e {
-        E::ApiError(err) => {
-            if let Some(code) = err.code {
-                let s = code.to_string();
-                if s.contains("401") { return AiError::Auth; }
-                if s.contains("429") { return AiError::RateLimited; }
-            }
-            AiError::Provider(err.message)
-        }
+        // async-openai hides the HTTP response, so classify by the error object alone
+        E::ApiError(err) => openai_error(
+            ErrorInfo::default(),
+            &json!({ "message": err.message, "type": err.r#type, "code": err.code }),
+        ),
         E::StreamError(m) => AiError::Provider(m),
-        E::Reqwest(e2) => {
-            if e2.is_timeout() { AiError::Timeout } else { AiError::Provider(e2.to_string()) }
-        }
+        E::Reqwest(e2) => map_reqwest_err(e2),
         other => AiError::Provider(other.to_string()),
     }
 }
 
-fn build_openai_request(
+/// The request as JSON, with multimodal user content in Chat Completions' content-part format
+/// (async-openai has no typed part for inline files).
+pub(crate) fn build_openai_body(config: &AskConfig, request: &AskRequest, stream: bool) -> Result<Value, AiError> {
+    let req = build_openai_request(config, request, stream)?;
+    let mut body = serde_json::to_value(req).map_err(|e| AiError::Json(e.to_string()))?;
+
+    // Each `Msg` maps to one message, after the optional system prompt
+    let offset = body["messages"].as_array().map_or(0, |m| m.len()).saturating_sub(request.messages.len());
+    for (i, m) in request.messages.iter().enumerate() {
+        if let Msg { role: Role::User, content: MsgContent::Parts(parts), .. } = m
+            && m.content.has_media()
+        {
+            body["messages"][i + offset]["content"] = parts.iter().map(openai_content_part).collect::<Result<_, _>>()?;
+        }
+    }
+    Ok(body)
+}
+
+fn openai_content_part(part: &ContentPart) -> Result<Value, AiError> {
+    Ok(match part {
+        ContentPart::Text { text } => json!({ "type": "text", "text": text }),
+        ContentPart::Image { source } => json!({ "type": "image_url", "image_url": { "url": source.to_url() } }),
+        ContentPart::Document { source: source @ MediaSource::Base64 { .. }, name } => json!({
+            "type": "file",
+            "file": { "filename": name.as_deref().unwrap_or("document.pdf"), "file_data": source.to_url() },
+        }),
+        // Chat Completions only takes inline files or uploaded file ids; the model cannot fetch a link
+        ContentPart::Document { source: MediaSource::Url { .. }, .. } => {
+            return Err(unsupported_part("Chat Completions", "document url (send it inline as base64)"));
+        }
+        ContentPart::Audio { data, format } => json!({ "type": "input_audio", "input_audio": { "data": data, "format": format } }),
+    })
+}
+
+pub(crate) fn build_openai_request(
     config: &AskConfig,
     request: &AskRequest,
+    stream: bool,
 ) -> Result<CreateChatCompletionRequest, AiError> {
+    // Only send what the backend accepts (OpenAI-compatible servers differ)
+    let caps = config.api.chat_capabilities();
+
     let mut oa_msgs: Vec<ChatCompletionRequestMessage> =
         Vec::with_capacity(request.messages.len() + 1);
 
-    if let Some(sys) = &request.system {
+    // Without native structured outputs the schema has to travel in the prompt
+    let schema_hint = request.options.json_schema.as_ref().filter(|_| !caps.json_schema).map(|spec| spec.instruction());
+    let system = match (&request.system, schema_hint) {
+        (Some(sys), Some(hint)) => Some(format!("{sys}\n\n{hint}")),
+        (sys, hint) => sys.clone().or(hint),
+    };
+    if let Some(sys) = &system {
         oa_msgs.push(
             ChatCompletionRequestSystemMessageArgs::default()
                 .content(sys.as_str())
@@ -222,29 +446,47 @@ fn build_openai_request(
 
     for m in &request.messages {
         match m {
+            // Media parts are filled in by `build_openai_body`
             Msg { role: Role::User, content, .. } => {
                 oa_msgs.push(
                     ChatCompletionRequestUserMessageArgs::default()
-                        .content(content.as_str())
+                        .content(content.text())
                         .build()
                         .map_err(|e| AiError::Provider(e.to_string()))?
                         .into(),
                 );
             }
-            Msg { role: Role::Assistant, content, .. } => {
+            Msg { role: Role::Assistant, content, tool_calls, .. } => {
+                let mut args = ChatCompletionRequestAssistantMessageArgs::default();
+                if !content.is_empty() || tool_calls.is_empty() {
+                    args.content(content.text());
+                }
+                if !tool_calls.is_empty() {
+                    args.tool_calls(
+                        tool_calls
+                            .iter()
+                            .map(|tc| ChatCompletionMessageToolCall {
+                                id: tc.id.clone(),
+                                r#type: ChatCompletionToolType::Function,
+                                function: FunctionCall {
+                                    name: tc.name.clone(),
+                                    arguments: tool_args_string(&tc.arguments),
+                                },
+                            })
+                            .collect::<Vec<_>>(),
+                    );
+                }
                 oa_msgs.push(
-                    ChatCompletionRequestAssistantMessageArgs::default()
-                        .content(content.clone())
-                        .build()
+                    args.build()
                         .map_err(|e| AiError::Provider(e.to_string()))?
                         .into(),
                 );
             }
-            Msg { role: Role::Tool, content, name } => {
+            Msg { role: Role::Tool, content, .. } => {
                 oa_msgs.push(
                     ChatCompletionRequestToolMessageArgs::default()
-                        .content(content.clone())
-                        .tool_call_id(name.clone().unwrap_or_default())
+                        .content(content.text())
+                        .tool_call_id(m.tool_call_id())
                         .build()
                         .map_err(|e| AiError::Provider(e.to_string()))?
                         .into(),
@@ -253,7 +495,7 @@ fn build_openai_request(
             Msg { role: Role::System, content, .. } => {
                 oa_msgs.push(
                     ChatCompletionRequestSystemMessageArgs::default()
-                        .content(content.as_str())
+                        .content(content.text())
                         .build()
                         .map_err(|e| AiError::Provider(e.to_string()))?
                         .into(),
@@ -264,15 +506,77 @@ fn build_openai_request(
 
     let mut builder = CreateChatCompletionRequestArgs::default();
     builder.model(config.model.as_str()).messages(oa_msgs);
+    if stream {
+        builder.stream(true);
+        if caps.stream_usage {
+            builder.stream_options(ChatCompletionStreamOptions { include_usage: true });
+        }
+    }
 
     if let Some(t) = request.options.temperature {
         builder.temperature(t);
     }
     if let Some(mx) = request.options.max_output_tokens {
-        builder.max_tokens(mx);
+        if caps.max_completion_tokens {
+            builder.max_completion_tokens(mx);
+        } else {
+            builder.max_tokens(mx);
+        }
+    }
+    if let Some(effort) = request.options.reasoning.as_ref().and_then(|r| r.effort) {
+        builder.reasoning_effort(match effort {
+            ReasoningEffort::Minimal => OaReasoningEffort::Minimal,
+            ReasoningEffort::Low => OaReasoningEffort::Low,
+            ReasoningEffort::Medium => OaReasoningEffort::Medium,
+            ReasoningEffort::High => OaReasoningEffort::High,
+        });
     }
-    if request.options.json_mode.unwrap_or(false) {
-        builder.response_format(ResponseFormat::JsonObject);
+    match &request.options.json_schema {
+        Some(spec) if caps.json_schema => {
+            builder.response_format(ResponseFormat::JsonSchema {
+                json_schema: ResponseFormatJsonSchema {
+                    description: spec.description.clone(),
+                    name: spec.name.clone(),
+                    schema: Some(if spec.strict { spec.strict_schema() } else { spec.schema.clone() }),
+                    strict: Some(spec.strict),
+                },
+            });
+        }
+        Some(_) if caps.response_format => {
+            builder.response_format(ResponseFormat::JsonObject);
+        }
+        None if caps.response_format && request.options.json_mode.unwrap_or(false) => {
+            builder.response_format(ResponseFormat::JsonObject);
+        }
+        _ => {}
+    }
+    if caps.tools && !request.tools.is_empty() {
+        builder.tools(
+            request
+                .tools
+                .iter()
+                .map(|t| ChatCompletionTool {
+                    r#type: ChatCompletionToolType::Function,
+                    function: FunctionObject {
+                        name: t.name.clone(),
+                        description: t.description.clone(),
+                        parameters: Some(t.parameters.clone()),
+                        strict: None,
+                    },
+                })
+                .collect::<Vec<_>>(),
+        );
+    }
+    if let Some(choice) = request.tool_choice.as_ref().filter(|_| caps.tools && !request.tools.is_empty()) {
+        builder.tool_choice(match choice {
+            ToolChoice::Auto => ChatCompletionToolChoiceOption::Auto,
+            ToolChoice::None => ChatCompletionToolChoiceOption::None,
+            ToolChoice::Required => ChatCompletionToolChoiceOption::Required,
+            ToolChoice::Tool(name) => ChatCompletionToolChoiceOption::Named(ChatCompletionNamedToolChoice {
+                r#type: ChatCompletionToolType::Function,
+                function: FunctionName { name: name.clone() },
+            }),
+        });
     }
 
     builder.build().map_err(|e| AiError::Provider(e.to_string()))
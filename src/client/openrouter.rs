@@ -0,0 +1,137 @@
+use futures_core::Stream;
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::ask::config::AskConfig;
+use crate::ask::request::{AskChunk, AskRequest};
+use crate::ask::response::AskResponse;
+use crate::client::openai::{build_openai_body, chat_chunk_stream, chat_response, parse_openai_response, send_chat};
+use crate::error::AiError;
+use crate::util::{endpoint, error_info, get_http_client, map_reqwest_err};
+
+/// OpenRouter-only request extensions (sent alongside the OpenAI-compatible body).
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenRouterOptions {
+    #[serde(default)]
+    pub provider: Option<ProviderPreferences>, // upstream routing preferences
+    #[serde(default)]
+    pub fallback_models: Vec<String>,          // tried in order if `model` fails
+    #[serde(default)]
+    pub transforms: Vec<String>,               // e.g. ["middle-out"]
+}
+
+/// Provider routing preferences, see https://openrouter.ai/docs/features/provider-routing
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct ProviderPreferences {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,                // provider slugs to try first, e.g. ["anthropic", "openai"]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_fallbacks: Option<bool>,     // allow providers outside `order`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_parameters: Option<bool>,  // only providers that support every request parameter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_collection: Option<String>,   // "allow" | "deny"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,              // "price" | "throughput" | "latency"
+}
+
+pub struct OpenRouterApi;
+
+impl OpenRouterApi {
+    pub async fn ask(
+        config: AskConfig,
+        request: &AskRequest,
+    ) -> Result<AskResponse, AiError> {
+        let body = build_openrouter_request(&config, request, false)?;
+        let resp = send_chat(&config, &body, attribution_headers(&config)?, false).await?;
+
+        Ok(parse_openai_response(&chat_response(resp).await?))
+    }
+
+    pub async fn ask_stream(
+        config: AskConfig,
+        request: &AskRequest,
+    ) -> Result<impl Stream<Item = Result<AskChunk, AiError>> + Send, AiError> {
+        let body = build_openrouter_request(&config, request, true)?;
+        let resp = send_chat(&config, &body, attribution_headers(&config)?, true).await?;
+
+        Ok(chat_chunk_stream(resp))
+    }
+
+    /// Model catalog with context length and per-token pricing (USD, as strings).
+    pub async fn get_models(config: &AskConfig) -> Result<Value, AiError> {
+        let http = get_http_client(config)?;
+        let resp = http
+            .get(endpoint(config, "models"))
+            .bearer_auth(&config.api_key)
+            .timeout(config.request_timeout)
+            .send()
+            .await
+            .map_err(map_reqwest_err)?;
+
+        if !resp.status().is_success() {
+            let mut info = error_info(&resp);
+            let body: Value = resp.json().await.unwrap_or(Value::Null);
+            info.message = body["error"]["message"].as_str().unwrap_or("model listing failed").to_string();
+            return Err(info.into_error());
+        }
+        let body: Value = resp.json().await.map_err(|e| AiError::Json(e.to_string()))?;
+
+        let models = body["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|m| json!({
+                "id": m["id"],
+                "name": m["name"],
+                "context_length": m["context_length"],
+                "pricing": m["pricing"],
+            }))
+            .collect::<Vec<_>>();
+
+        Ok(Value::Array(models))
+    }
+}
+
+/// The OpenAI-compatible body plus OpenRouter's routing extensions.
+fn build_openrouter_request(config: &AskConfig, request: &AskRequest, stream: bool) -> Result<Value, AiError> {
+    let mut body = build_openai_body(config, request, stream)?;
+
+    if let Some(opts) = &request.options.openrouter {
+        if let Some(provider) = &opts.provider {
+            body["provider"] = json!(provider);
+        }
+        if !opts.fallback_models.is_empty() {
+            let mut models = vec![config.model.clone()];
+            models.extend(opts.fallback_models.iter().cloned());
+            body["models"] = json!(models);
+        }
+        if !opts.transforms.is_empty() {
+            body["transforms"] = json!(opts.transforms);
+        }
+    }
+    Ok(body)
+}
+
+/// `HTTP-Referer` / `X-Title` from `AskConfig.app_url` / `app_name`, for OpenRouter's app rankings.
+fn attribution_headers(config: &AskConfig) -> Result<HeaderMap, AiError> {
+    let mut headers = HeaderMap::new();
+    if let Some(app_url) = &config.app_url {
+        headers.insert("HTTP-Referer", HeaderValue::from_str(app_url).map_err(|e| invalid_header("app_url", e))?);
+    }
+    if let Some(app_name) = &config.app_name {
+        headers.insert("X-Title", HeaderValue::from_str(app_name).map_err(|e| invalid_header("app_name", e))?);
+    }
+    Ok(headers)
+}
+
+/// A config mistake, not a network failure: retrying or falling back cannot fix it.
+fn invalid_header(field: &str, e: reqwest::header::InvalidHeaderValue) -> AiError {
+    AiError::Provider(format!("invalid {field} for an HTTP header: {e}"))
+}
@@ -0,0 +1,257 @@
+//! The `Provider` trait every backend implements, and the registry custom providers plug into.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use async_trait::async_trait;
+use futures_core::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ask::config::AskConfig;
+use crate::ask::request::{AskChunk, AskRequest};
+use crate::ask::response::AskResponse;
+use crate::client::anthropic::AnthropicApi;
+use crate::client::gemini::GeminiApi;
+use crate::client::ollama::OllamaApi;
+use crate::client::openai::OpenAiApi;
+use crate::client::openrouter::OpenRouterApi;
+use crate::client::ProviderAPI;
+use crate::error::AiError;
+
+/// Boxed chunk stream returned by `Provider::ask_stream`.
+pub type AskStream<'a> = Pin<Box<dyn Stream<Item = Result<AskChunk, AiError>> + Send + 'a>>;
+
+/// What a provider supports, so callers can skip features instead of trying them.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCapabilities {
+    pub streaming: bool,
+    pub tools: bool,
+    pub json_mode: bool,
+    pub embeddings: bool,
+    pub model_listing: bool,
+}
+
+/// A chat backend. Implement this to plug an in-house gateway into `CnctdAi`.
+#[async_trait]
+pub trait Provider: Send + Sync {
+    async fn ask(&self, config: AskConfig, request: &AskRequest) -> Result<AskResponse, AiError>;
+
+    async fn ask_stream<'a>(&self, config: AskConfig, request: &'a AskRequest) -> Result<AskStream<'a>, AiError>;
+
+    async fn list_models(&self, config: &AskConfig) -> Result<Value, AiError>;
+
+    async fn embed(&self, _config: &AskConfig, _text: &str, _model: Option<&str>) -> Result<Vec<f32>, AiError> {
+        Err(AiError::Unsupported)
+    }
+
+    /// What this provider supports when called with `config` (one adapter can serve several
+    /// backends, e.g. the OpenAI one for every `OpenAiCompatible` profile).
+    fn capabilities(&self, config: &AskConfig) -> ProviderCapabilities;
+}
+
+/// Custom providers keyed by string id. `AskRequest.provider` and `ProviderAPI::Custom(id)`
+/// resolve against it before falling back to the built-in adapters.
+#[derive(Clone, Default)]
+pub struct ProviderRegistry {
+    providers: Arc<RwLock<HashMap<String, Arc<dyn Provider>>>>,
+}
+
+static GLOBAL_REGISTRY: LazyLock<ProviderRegistry> = LazyLock::new(ProviderRegistry::default);
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide registry used by `CnctdAi`.
+    pub fn global() -> &'static ProviderRegistry {
+        &GLOBAL_REGISTRY
+    }
+
+    /// Register (or replace) a provider. Ids are case-insensitive.
+    pub fn register(&self, id: impl Into<String>, provider: Arc<dyn Provider>) {
+        let mut providers = self.providers.write().unwrap_or_else(|e| e.into_inner());
+        providers.insert(id.into().to_lowercase(), provider);
+    }
+
+    pub fn unregister(&self, id: &str) -> Option<Arc<dyn Provider>> {
+        let mut providers = self.providers.write().unwrap_or_else(|e| e.into_inner());
+        providers.remove(&id.to_lowercase())
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<dyn Provider>> {
+        let providers = self.providers.read().unwrap_or_else(|e| e.into_inner());
+        providers.get(&id.to_lowercase()).cloned()
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        let providers = self.providers.read().unwrap_or_else(|e| e.into_inner());
+        providers.keys().cloned().collect()
+    }
+
+    /// Pick the provider for a call: a registered `request_provider` id wins, then the
+    /// config's `ProviderAPI` (custom ids must be registered).
+    pub fn resolve(&self, request_provider: Option<&str>, api: &ProviderAPI) -> Result<Arc<dyn Provider>, AiError> {
+        if let Some(provider) = request_provider.filter(|id| !id.is_empty()).and_then(|id| self.get(id)) {
+            return Ok(provider);
+        }
+        match api {
+            ProviderAPI::Custom(id) => self.get(id).ok_or(AiError::Unsupported),
+            api => builtin_provider(api).ok_or(AiError::Unsupported),
+        }
+    }
+}
+
+/// The adapter shipped with this crate for `api`.
+pub fn builtin_provider(api: &ProviderAPI) -> Option<Arc<dyn Provider>> {
+    match api {
+        ProviderAPI::OpenAI | ProviderAPI::OpenAiCompatible { .. } => Some(Arc::new(OpenAiApi)),
+        ProviderAPI::Anthropic => Some(Arc::new(AnthropicApi)),
+        ProviderAPI::OpenRouter => Some(Arc::new(OpenRouterApi)),
+        ProviderAPI::Gemini => Some(Arc::new(GeminiApi)),
+        ProviderAPI::Ollama => Some(Arc::new(OllamaApi)),
+        ProviderAPI::Custom(_) => None,
+    }
+}
+
+#[async_trait]
+impl Provider for OpenAiApi {
+    async fn ask(&self, config: AskConfig, request: &AskRequest) -> Result<AskResponse, AiError> {
+        OpenAiApi::ask(config, request).await
+    }
+
+    async fn ask_stream<'a>(&self, config: AskConfig, request: &'a AskRequest) -> Result<AskStream<'a>, AiError> {
+        Ok(Box::pin(OpenAiApi::ask_stream(config, request).await?))
+    }
+
+    async fn list_models(&self, config: &AskConfig) -> Result<Value, AiError> {
+        OpenAiApi::get_models(config).await
+    }
+
+    async fn embed(&self, config: &AskConfig, text: &str, model: Option<&str>) -> Result<Vec<f32>, AiError> {
+        OpenAiApi::get_embedding(text, config, model).await
+    }
+
+    fn capabilities(&self, config: &AskConfig) -> ProviderCapabilities {
+        let chat = config.api.chat_capabilities();
+        ProviderCapabilities {
+            streaming: true,
+            tools: chat.tools,
+            json_mode: chat.response_format || chat.json_schema,
+            embeddings: true,
+            model_listing: true,
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for AnthropicApi {
+    async fn ask(&self, config: AskConfig, request: &AskRequest) -> Result<AskResponse, AiError> {
+        AnthropicApi::ask(config, request).await
+    }
+
+    async fn ask_stream<'a>(&self, config: AskConfig, request: &'a AskRequest) -> Result<AskStream<'a>, AiError> {
+        Ok(Box::pin(AnthropicApi::ask_stream(config, request).await?))
+    }
+
+    async fn list_models(&self, config: &AskConfig) -> Result<Value, AiError> {
+        AnthropicApi::get_models(config).await
+    }
+
+    fn capabilities(&self, _config: &AskConfig) -> ProviderCapabilities {
+        ProviderCapabilities {
+            streaming: true,
+            tools: true,
+            json_mode: true,
+            embeddings: false,
+            model_listing: true,
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for OpenRouterApi {
+    async fn ask(&self, config: AskConfig, request: &AskRequest) -> Result<AskResponse, AiError> {
+        OpenRouterApi::ask(config, request).await
+    }
+
+    async fn ask_stream<'a>(&self, config: AskConfig, request: &'a AskRequest) -> Result<AskStream<'a>, AiError> {
+        Ok(Box::pin(OpenRouterApi::ask_stream(config, request).await?))
+    }
+
+    async fn list_models(&self, config: &AskConfig) -> Result<Value, AiError> {
+        OpenRouterApi::get_models(config).await
+    }
+
+    fn capabilities(&self, config: &AskConfig) -> ProviderCapabilities {
+        let chat = config.api.chat_capabilities();
+        ProviderCapabilities {
+            streaming: true,
+            tools: chat.tools,
+            json_mode: chat.response_format || chat.json_schema,
+            embeddings: false,
+            model_listing: true,
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for GeminiApi {
+    async fn ask(&self, config: AskConfig, request: &AskRequest) -> Result<AskResponse, AiError> {
+        GeminiApi::ask(config, request).await
+    }
+
+    async fn ask_stream<'a>(&self, config: AskConfig, request: &'a AskRequest) -> Result<AskStream<'a>, AiError> {
+        Ok(Box::pin(GeminiApi::ask_stream(config, request).await?))
+    }
+
+    async fn list_models(&self, config: &AskConfig) -> Result<Value, AiError> {
+        GeminiApi::get_models(config).await
+    }
+
+    async fn embed(&self, config: &AskConfig, text: &str, model: Option<&str>) -> Result<Vec<f32>, AiError> {
+        GeminiApi::get_embedding(text, config, model).await
+    }
+
+    fn capabilities(&self, _config: &AskConfig) -> ProviderCapabilities {
+        ProviderCapabilities {
+            streaming: true,
+            tools: true,
+            json_mode: true,
+            embeddings: true,
+            model_listing: true,
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for OllamaApi {
+    async fn ask(&self, config: AskConfig, request: &AskRequest) -> Result<AskResponse, AiError> {
+        OllamaApi::ask(config, request).await
+    }
+
+    async fn ask_stream<'a>(&self, config: AskConfig, request: &'a AskRequest) -> Result<AskStream<'a>, AiError> {
+        Ok(Box::pin(OllamaApi::ask_stream(config, request).await?))
+    }
+
+    async fn list_models(&self, config: &AskConfig) -> Result<Value, AiError> {
+        OllamaApi::get_models(config).await
+    }
+
+    async fn embed(&self, config: &AskConfig, text: &str, model: Option<&str>) -> Result<Vec<f32>, AiError> {
+        OllamaApi::get_embedding(text, config, model).await
+    }
+
+    fn capabilities(&self, _config: &AskConfig) -> ProviderCapabilities {
+        ProviderCapabilities {
+            streaming: true,
+            tools: true,
+            json_mode: true,
+            embeddings: true,
+            model_listing: true,
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Client-side request and token budgets, shared by every call with the same
+//! `(ProviderAPI, model, api_key)`.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+use crate::ask::config::AskConfig;
+use crate::ask::request::AskRequest;
+use crate::ask::response::AskResponse;
+use crate::client::ProviderAPI;
+use crate::error::AiError;
+
+/// Per-minute budgets. Unset limits are learned from the provider's rate-limit headers where
+/// it sends them (`RateLimit::default()` is fully adaptive).
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default)]
+    pub tokens_per_minute: Option<u32>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct LimiterKey {
+    api: ProviderAPI,
+    model: String,
+    key_hash: u64, // the api key itself is not kept
+}
+
+static LIMITERS: LazyLock<Mutex<HashMap<LimiterKey, Arc<Mutex<Limiter>>>>> = LazyLock::new(Default::default);
+
+/// Token bucket refilled continuously up to one minute's worth.
+struct Bucket {
+    capacity: f64,
+    available: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(per_minute: u32) -> Self {
+        Self {
+            capacity: per_minute as f64,
+            available: per_minute as f64,
+            updated: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let per_sec = self.capacity / 60.0;
+        self.available = (self.available + now.duration_since(self.updated).as_secs_f64() * per_sec).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// How long until `amount` fits; requests larger than the whole budget only wait for a full bucket.
+    fn wait_for(&self, amount: f64) -> Duration {
+        let missing = amount.min(self.capacity) - self.available;
+        if missing <= 0.0 || self.capacity <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(missing / (self.capacity / 60.0))
+        }
+    }
+}
+
+#[derive(Default)]
+struct Limiter {
+    configured: RateLimit,
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+    blocked_until: Option<Instant>, // set from a 429's `retry-after`
+}
+
+impl Limiter {
+    fn new(limit: &RateLimit) -> Self {
+        Self {
+            configured: limit.clone(),
+            requests: limit.requests_per_minute.map(Bucket::new),
+            tokens: limit.tokens_per_minute.map(Bucket::new),
+            blocked_until: None,
+        }
+    }
+
+    /// Take one request and `tokens` if both fit now, otherwise say how long to wait.
+    fn try_take(&mut self, tokens: u32) -> Duration {
+        let now = Instant::now();
+        if let Some(until) = self.blocked_until {
+            if until > now {
+                return until - now;
+            }
+            self.blocked_until = None;
+        }
+
+        let mut wait = Duration::ZERO;
+        if let Some(b) = &mut self.requests {
+            b.refill(now);
+            wait = wait.max(b.wait_for(1.0));
+        }
+        if let Some(b) = &mut self.tokens {
+            b.refill(now);
+            wait = wait.max(b.wait_for(tokens as f64));
+        }
+        if wait.is_zero() {
+            if let Some(b) = &mut self.requests {
+                b.available -= 1.0;
+            }
+            if let Some(b) = &mut self.tokens {
+                b.available -= tokens as f64;
+            }
+        }
+        wait
+    }
+}
+
+fn limiter_for(config: &AskConfig) -> Option<Arc<Mutex<Limiter>>> {
+    let limit = config.rate_limit.as_ref()?;
+    let mut hasher = DefaultHasher::new();
+    config.api_key.hash(&mut hasher);
+    let key = LimiterKey {
+        api: config.api.clone(),
+        model: config.model.clone(),
+        key_hash: hasher.finish(),
+    };
+
+    let mut limiters = LIMITERS.lock().unwrap_or_else(|e| e.into_inner());
+    let limiter = limiters.entry(key).or_insert_with(|| Arc::new(Mutex::new(Limiter::new(limit))));
+    {
+        // A changed configuration starts over with fresh buckets
+        let mut l = limiter.lock().unwrap_or_else(|e| e.into_inner());
+        if l.configured != *limit {
+            *l = Limiter::new(limit);
+        }
+    }
+    Some(limiter.clone())
+}
+
+/// Wait until one request of about `tokens` tokens fits the budget of `config`.
+pub(crate) async fn acquire(config: &AskConfig, tokens: u32) {
+    let Some(limiter) = limiter_for(config) else { return };
+    loop {
+        let wait = limiter.lock().unwrap_or_else(|e| e.into_inner()).try_take(tokens);
+        if wait.is_zero() {
+            return;
+        }
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Correct the token budget once the provider has reported what the call really used.
+pub(crate) fn settle(config: &AskConfig, estimated: u32, response: &AskResponse) {
+    let Some(usage) = &response.usage else { return };
+    let Some(actual) = usage.total_tokens.or(usage.prompt_tokens.zip(usage.completion_tokens).map(|(p, c)| p + c)) else {
+        return;
+    };
+    let Some(limiter) = limiter_for(config) else { return };
+    let mut l = limiter.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(b) = &mut l.tokens {
+        b.available -= actual as f64 - estimated as f64;
+    }
+}
+
+/// After a 429 with a `retry-after` hint, hold back every caller sharing this budget.
+pub(crate) fn observe_error(config: &AskConfig, error: &AiError) {
+    let AiError::RateLimited(info) = error else { return };
+    let Some(retry_after) = info.retry_after else { return };
+    let Some(limiter) = limiter_for(config) else { return };
+    let mut l = limiter.lock().unwrap_or_else(|e| e.into_inner());
+    let until = Instant::now() + retry_after;
+    l.blocked_until = Some(l.blocked_until.map_or(until, |current| current.max(until)));
+}
+
+/// Sync the budget with the provider's rate-limit headers (Anthropic's `anthropic-ratelimit-*`
+/// or the OpenAI-style `x-ratelimit-*`). The provider's count wins when it is lower, e.g.
+/// because other processes share the key.
+pub(crate) fn observe_headers(config: &AskConfig, headers: &HeaderMap) {
+    let Some(limiter) = limiter_for(config) else { return };
+    let num = |names: [&str; 2]| {
+        names
+            .iter()
+            .find_map(|name| headers.get(*name).and_then(|v| v.to_str().ok()).and_then(|v| v.trim().parse::<u32>().ok()))
+    };
+
+    let mut l = limiter.lock().unwrap_or_else(|e| e.into_inner());
+    let l = &mut *l;
+    for (bucket, configured, limit, remaining) in [
+        (
+            &mut l.requests,
+            l.configured.requests_per_minute,
+            num(["anthropic-ratelimit-requests-limit", "x-ratelimit-limit-requests"]),
+            num(["anthropic-ratelimit-requests-remaining", "x-ratelimit-remaining-requests"]),
+        ),
+        (
+            &mut l.tokens,
+            l.configured.tokens_per_minute,
+            num(["anthropic-ratelimit-tokens-limit", "x-ratelimit-limit-tokens"]),
+            num(["anthropic-ratelimit-tokens-remaining", "x-ratelimit-remaining-tokens"]),
+        ),
+    ] {
+        if configured.is_none()
+            && let Some(limit) = limit
+            && bucket.as_ref().is_none_or(|b| b.capacity != limit as f64)
+        {
+            *bucket = Some(Bucket::new(limit));
+        }
+        if let (Some(b), Some(remaining)) = (bucket.as_mut(), remaining) {
+            b.refill(Instant::now());
+            b.available = b.available.min(remaining as f64);
+        }
+    }
+}
+
+/// Rough token count for budgeting before the provider reports usage (~4 chars per token).
+pub(crate) fn estimate_tokens(request: &AskRequest) -> u32 {
+    let chars: usize = request.system.as_ref().map_or(0, String::len)
+        + request.messages.iter().map(|m| m.content.text().len()).sum::<usize>();
+    estimate_text_tokens(chars) + request.options.max_output_tokens.unwrap_or(0)
+}
+
+pub(crate) fn estimate_text_tokens(chars: usize) -> u32 {
+    (chars / 4).try_into().unwrap_or(u32::MAX)
+}
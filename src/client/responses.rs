@@ -0,0 +1,346 @@
+//! OpenAI Responses API (`/responses`), the alternative to Chat Completions behind `OpenAiApi`.
+
+use std::collections::HashMap;
+
+use async_stream::try_stream;
+use futures_core::Stream;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    ask::{
+        config::AskConfig,
+        msg::{ContentPart, MediaSource, Msg, MsgContent, Role, ThinkingBlock},
+        request::{AskChunk, AskRequest},
+        response::{AskResponse, Usage},
+        tool::{ToolCall, ToolChoice},
+    },
+    client::{
+        openai::{openai_error, tool_args_string},
+        ratelimit,
+        sse::sse_events,
+    },
+    error::{AiError, ErrorInfo},
+    util::{endpoint, error_info, get_http_client, map_reqwest_err, parse_tool_args, unsupported_part},
+};
+
+/// Responses-only request extensions (used when `AskConfig.openai_backend` is `Responses`).
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponsesOptions {
+    #[serde(default)]
+    pub previous_response_id: Option<String>, // an earlier `AskResponse.response_id`; then only send the new messages
+    #[serde(default)]
+    pub store: Option<bool>,                  // keep the response server-side so it can be chained (OpenAI default: true)
+    #[serde(default)]
+    pub reasoning_summary: Option<String>,    // "auto" | "concise" | "detailed"; returned as thinking
+}
+
+pub struct ResponsesApi;
+
+impl ResponsesApi {
+    pub async fn ask(config: AskConfig, request: &AskRequest) -> Result<AskResponse, AiError> {
+        let resp = send_responses(&config, request, false).await?;
+        let body: Value = resp.json().await.map_err(|e| AiError::Json(e.to_string()))?;
+        Ok(parse_response(body))
+    }
+
+    /// Maps the typed stream events onto `AskChunk`s, ending with `AskChunk::Complete`, or with an
+    /// error if the stream stops before a terminal event.
+    pub async fn ask_stream(
+        config: AskConfig,
+        request: &AskRequest,
+    ) -> Result<impl Stream<Item = Result<AskChunk, AiError>> + Send, AiError> {
+        let resp = send_responses(&config, request, true).await?;
+        let mut events = Box::pin(sse_events(resp));
+
+        let s = try_stream! {
+            // Argument deltas only carry the output index; remember which call lives there
+            let mut call_ids: HashMap<u64, String> = HashMap::new();
+            let mut finished = false;
+
+            while let Some(event) = events.next().await {
+                let event = event?;
+                if event.data.is_empty() {
+                    continue;
+                }
+                let data: Value = serde_json::from_str(&event.data).map_err(|e| AiError::Json(e.to_string()))?;
+                let index = data["output_index"].as_u64().unwrap_or_default();
+
+                match data["type"].as_str().unwrap_or_default() {
+                    "response.output_text.delta" | "response.refusal.delta" => {
+                        yield AskChunk::Delta { text: data["delta"].as_str().unwrap_or_default().to_string() };
+                    }
+                    "response.reasoning_summary_text.delta" => {
+                        yield AskChunk::Thinking { text: data["delta"].as_str().unwrap_or_default().to_string() };
+                    }
+                    "response.output_item.added" if data["item"]["type"] == "function_call" => {
+                        let id = data["item"]["call_id"].as_str().unwrap_or_default().to_string();
+                        call_ids.insert(index, id.clone());
+                        yield AskChunk::ToolCallDelta {
+                            index: index as u32,
+                            tool_call_id: id,
+                            name: data["item"]["name"].as_str().map(str::to_string),
+                            args_delta: None,
+                        };
+                    }
+                    "response.function_call_arguments.delta" => {
+                        yield AskChunk::ToolCallDelta {
+                            index: index as u32,
+                            tool_call_id: call_ids.get(&index).cloned().unwrap_or_default(),
+                            name: None,
+                            args_delta: data["delta"].as_str().map(str::to_string),
+                        };
+                    }
+                    "response.completed" | "response.incomplete" => {
+                        yield AskChunk::Complete(parse_response(data["response"].clone()));
+                        finished = true;
+                        break;
+                    }
+                    "response.failed" => Err(openai_error(ErrorInfo::default(), &data["response"]["error"]))?,
+                    "error" => Err(openai_error(ErrorInfo::default(), &data))?,
+                    _ => {} // lifecycle events and item kinds we do not surface
+                }
+            }
+
+            // A dropped connection can end the body cleanly; do not pass that off as a full answer
+            if !finished {
+                Err(AiError::Http("stream ended before the response completed".to_string()))?;
+            }
+        };
+
+        Ok(s)
+    }
+}
+
+/// POST the request to `{config.url}/responses` (Azure: `{endpoint}/openai/responses`).
+async fn send_responses(config: &AskConfig, request: &AskRequest, stream: bool) -> Result<reqwest::Response, AiError> {
+    let http = get_http_client(config)?;
+    let body = build_responses_body(config, request, stream)?;
+
+    let builder = match &config.azure {
+        Some(azure) => http
+            .post(endpoint(config, "openai/responses"))
+            .query(&[("api-version", azure.api_version.as_str())])
+            .header("api-key", &config.api_key),
+        None => http.post(endpoint(config, "responses")).bearer_auth(&config.api_key),
+    };
+    let mut builder = builder.json(&body);
+    if !stream {
+        builder = builder.timeout(config.request_timeout);
+    }
+
+    let resp = builder.send().await.map_err(map_reqwest_err)?;
+    ratelimit::observe_headers(config, resp.headers());
+    if !resp.status().is_success() {
+        let info = error_info(&resp);
+        let body: Value = resp.json().await.unwrap_or(Value::Null);
+        return Err(openai_error(info, &body["error"]));
+    }
+    Ok(resp)
+}
+
+fn build_responses_body(config: &AskConfig, request: &AskRequest, stream: bool) -> Result<Value, AiError> {
+    let model = match &config.azure {
+        Some(azure) => azure.deployment_for(&config.model),
+        None => config.model.clone(),
+    };
+    let mut body = json!({
+        "model": model,
+        "input": build_responses_input(request)?,
+        "stream": stream,
+    });
+
+    if let Some(system) = &request.system {
+        body["instructions"] = json!(system);
+    }
+    if let Some(mx) = request.options.max_output_tokens {
+        body["max_output_tokens"] = json!(mx);
+    }
+    if let Some(t) = request.options.temperature {
+        body["temperature"] = json!(t);
+    }
+
+    let options = request.options.responses.clone().unwrap_or_default();
+    if let Some(id) = &options.previous_response_id {
+        body["previous_response_id"] = json!(id);
+    }
+    if let Some(store) = options.store {
+        body["store"] = json!(store);
+    }
+    let effort = request.options.reasoning.as_ref().and_then(|r| r.effort);
+    if effort.is_some() || options.reasoning_summary.is_some() {
+        let mut reasoning = json!({});
+        if let Some(effort) = effort {
+            reasoning["effort"] = json!(effort);
+        }
+        if let Some(summary) = &options.reasoning_summary {
+            reasoning["summary"] = json!(summary);
+        }
+        body["reasoning"] = reasoning;
+    }
+
+    match &request.options.json_schema {
+        Some(spec) => {
+            let mut format = json!({
+                "type": "json_schema",
+                "name": spec.name,
+                "schema": if spec.strict { spec.strict_schema() } else { spec.schema.clone() },
+                "strict": spec.strict,
+            });
+            if let Some(desc) = &spec.description {
+                format["description"] = json!(desc);
+            }
+            body["text"] = json!({ "format": format });
+        }
+        None if request.options.json_mode.unwrap_or(false) => {
+            body["text"] = json!({ "format": { "type": "json_object" } });
+        }
+        None => {}
+    }
+
+    if !request.tools.is_empty() {
+        body["tools"] = request
+            .tools
+            .iter()
+            .map(|t| {
+                let mut tool = json!({ "type": "function", "name": t.name, "parameters": t.parameters, "strict": false });
+                if let Some(desc) = &t.description {
+                    tool["description"] = json!(desc);
+                }
+                tool
+            })
+            .collect();
+        if let Some(choice) = &request.tool_choice {
+            body["tool_choice"] = match choice {
+                ToolChoice::Auto => json!("auto"),
+                ToolChoice::None => json!("none"),
+                ToolChoice::Required => json!("required"),
+                ToolChoice::Tool(name) => json!({ "type": "function", "name": name }),
+            };
+        }
+    }
+    Ok(body)
+}
+
+/// Messages become input items; tool calls and their results are items of their own.
+fn build_responses_input(request: &AskRequest) -> Result<Vec<Value>, AiError> {
+    let mut input = Vec::with_capacity(request.messages.len());
+    for m in &request.messages {
+        match m {
+            Msg { role: Role::User, content, .. } => {
+                input.push(json!({ "role": "user", "content": responses_content(content)? }));
+            }
+            Msg { role: Role::Assistant, content, tool_calls, .. } => {
+                if !content.is_empty() || tool_calls.is_empty() {
+                    input.push(json!({ "role": "assistant", "content": content.text() }));
+                }
+                for tc in tool_calls {
+                    input.push(json!({
+                        "type": "function_call",
+                        "call_id": tc.id,
+                        "name": tc.name,
+                        "arguments": tool_args_string(&tc.arguments),
+                    }));
+                }
+            }
+            Msg { role: Role::Tool, content, .. } => {
+                input.push(json!({ "type": "function_call_output", "call_id": m.tool_call_id(), "output": content.text() }));
+            }
+            Msg { role: Role::System, content, .. } => {
+                input.push(json!({ "role": "system", "content": content.text() }));
+            }
+        }
+    }
+    Ok(input)
+}
+
+/// Plain text stays a string; parts become `input_*` content items.
+fn responses_content(content: &MsgContent) -> Result<Value, AiError> {
+    let MsgContent::Parts(parts) = content else {
+        return Ok(json!(content.text()));
+    };
+    parts
+        .iter()
+        .map(|part| match part {
+            ContentPart::Text { text } => Ok(json!({ "type": "input_text", "text": text })),
+            ContentPart::Image { source } => Ok(json!({ "type": "input_image", "image_url": source.to_url() })),
+            ContentPart::Document { source: source @ MediaSource::Base64 { .. }, name } => Ok(json!({
+                "type": "input_file",
+                "filename": name.as_deref().unwrap_or("document.pdf"),
+                "file_data": source.to_url(),
+            })),
+            ContentPart::Document { source: MediaSource::Url { url, .. }, .. } => {
+                Ok(json!({ "type": "input_file", "file_url": url }))
+            }
+            ContentPart::Audio { .. } => Err(unsupported_part("the OpenAI Responses API", "audio")),
+        })
+        .collect()
+}
+
+/// Normalize a response object: message text, function calls, reasoning summaries and usage.
+fn parse_response(body: Value) -> AskResponse {
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    let mut thinking = Vec::new();
+    for item in body["output"].as_array().into_iter().flatten() {
+        match item["type"].as_str() {
+            Some("message") => {
+                for part in item["content"].as_array().into_iter().flatten() {
+                    match part["type"].as_str() {
+                        Some("output_text") => text.push_str(part["text"].as_str().unwrap_or_default()),
+                        Some("refusal") => text.push_str(part["refusal"].as_str().unwrap_or_default()),
+                        _ => {}
+                    }
+                }
+            }
+            Some("function_call") => tool_calls.push(ToolCall {
+                id: item["call_id"].as_str().unwrap_or_default().to_string(),
+                name: item["name"].as_str().unwrap_or_default().to_string(),
+                arguments: parse_tool_args(item["arguments"].as_str().unwrap_or_default()),
+            }),
+            Some("reasoning") => {
+                for summary in item["summary"].as_array().into_iter().flatten() {
+                    thinking.push(ThinkingBlock::Thinking {
+                        text: summary["text"].as_str().unwrap_or_default().to_string(),
+                        signature: None,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let finish_reason = match body["status"].as_str() {
+        Some("incomplete") if body["incomplete_details"]["reason"] == "content_filter" => "content_filter",
+        Some("incomplete") => "length",
+        Some("failed") => "error",
+        _ if !tool_calls.is_empty() => "tool_call",
+        _ => "stop",
+    };
+
+    let usage = body.get("usage").filter(|u| u.is_object()).map(|u| {
+        let count = |v: &Value| v.as_u64().map(|n| n as u32);
+        Usage {
+            prompt_tokens: count(&u["input_tokens"]),
+            completion_tokens: count(&u["output_tokens"]),
+            total_tokens: count(&u["total_tokens"]),
+            cached_tokens: count(&u["input_tokens_details"]["cached_tokens"]),
+            reasoning_tokens: count(&u["output_tokens_details"]["reasoning_tokens"]),
+            ..Default::default()
+        }
+    });
+
+    AskResponse {
+        text,
+        finish_reason: finish_reason.to_string(),
+        usage,
+        response_id: body["id"].as_str().map(str::to_string),
+        model: body["model"].as_str().unwrap_or_default().to_string(),
+        tool_calls,
+        thinking,
+        provider_meta: body,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,272 @@
+//! Retry policy for transient provider failures, and the call path `CnctdAi` and `Client` share.
+
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::ask::config::AskConfig;
+use crate::ask::request::{AskChunk, AskRequest};
+use crate::ask::response::AskResponse;
+use crate::client::cost::PricingTable;
+use crate::client::provider::{AskStream, Provider};
+use crate::client::ratelimit;
+use crate::error::AiError;
+
+/// Error kinds a `RetryPolicy` may retry.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RetryOn {
+    RateLimited, // 429
+    Overloaded,  // 503 / 529
+    ServerError, // other 5xx
+    Timeout,
+    Connection, // no HTTP response, or the connection dropped mid-body
+}
+
+impl RetryOn {
+    pub fn matches(&self, error: &AiError) -> bool {
+        matches!(
+            (self, error),
+            (RetryOn::RateLimited, AiError::RateLimited(_))
+                | (RetryOn::Overloaded, AiError::Overloaded(_))
+                | (RetryOn::ServerError, AiError::ServerError(_))
+                | (RetryOn::Timeout, AiError::Timeout)
+                | (RetryOn::Connection, AiError::Http(_))
+        )
+    }
+}
+
+/// Exponential backoff with jitter. A server `retry-after` hint replaces the computed delay.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryPolicy {
+    pub max_attempts: u32,    // total tries, including the first
+    pub base_delay: Duration, // first backoff; doubles on every retry
+    pub max_delay: Duration,  // cap on backoff; a longer `retry-after` fails the call instead of waiting
+    pub jitter: f64,          // fraction of each delay that is randomized, 0.0 - 1.0
+    pub retry_on: Vec<RetryOn>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.5,
+            retry_on: vec![
+                RetryOn::RateLimited,
+                RetryOn::Overloaded,
+                RetryOn::ServerError,
+                RetryOn::Timeout,
+                RetryOn::Connection,
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self { max_attempts, ..Self::default() }
+    }
+
+    /// Wait before the next try after `attempts` failed tries, or `None` to give up.
+    pub fn delay(&self, attempts: u32, error: &AiError) -> Option<Duration> {
+        if attempts >= self.max_attempts || !self.retry_on.iter().any(|kind| kind.matches(error)) {
+            return None;
+        }
+        if let Some(hint) = error.retry_after() {
+            return (hint <= self.max_delay).then_some(hint);
+        }
+
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+            .min(self.max_delay);
+        Some(backoff.mul_f64(1.0 - self.jitter.clamp(0.0, 1.0) * fastrand::f64()))
+    }
+}
+
+/// `provider.ask`, throttled per `config.rate_limit` and retried per `config.retry`. Latency is
+/// measured from `started`, so a fallback chain can count the entries that failed before this one.
+pub(crate) async fn ask_with_retry(
+    provider: &dyn Provider,
+    config: AskConfig,
+    request: &AskRequest,
+    started: Instant,
+) -> Result<AskResponse, AiError> {
+    let estimated = ratelimit::estimate_tokens(request);
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        ratelimit::acquire(&config, estimated).await;
+        let err = match provider.ask(config.clone(), request).await {
+            Ok(mut response) => {
+                ratelimit::settle(&config, estimated, &response);
+                stamp(&mut response, &config, started);
+                return Ok(response);
+            }
+            Err(e) => e,
+        };
+        ratelimit::observe_error(&config, &err);
+        match config.retry.as_ref().and_then(|policy| policy.delay(attempts, &err)) {
+            Some(delay) => tokio::time::sleep(delay).await,
+            None => return Err(err),
+        }
+    }
+}
+
+/// `provider.ask_stream`, throttled per `config.rate_limit` and retried per `config.retry` until
+/// the first chunk arrives; after that errors are passed through, since the caller may already
+/// have used part of the answer. A failed first chunk is returned as the error, so callers can
+/// fall back on it.
+pub(crate) async fn ask_stream_with_retry<'a>(
+    provider: &dyn Provider,
+    config: AskConfig,
+    request: &'a AskRequest,
+    started: Instant,
+) -> Result<AskStream<'a>, AiError> {
+    let estimated = ratelimit::estimate_tokens(request);
+    let stamped = |stream: AskStream<'a>| -> AskStream<'a> {
+        let config = config.clone();
+        let mut first_token = None;
+        Box::pin(stream.map(move |chunk| match chunk {
+            Ok(AskChunk::Complete(mut response)) => {
+                ratelimit::settle(&config, estimated, &response);
+                stamp(&mut response, &config, started);
+                record_stream_timing(&mut response, started, first_token);
+                Ok(AskChunk::Complete(response))
+            }
+            Ok(chunk) => {
+                let generated = match &chunk {
+                    AskChunk::Delta { text } | AskChunk::Thinking { text } => !text.is_empty(),
+                    AskChunk::ToolCallDelta { .. } | AskChunk::ToolCall(_) => true,
+                    _ => false,
+                };
+                if generated {
+                    first_token.get_or_insert_with(Instant::now);
+                }
+                Ok(chunk)
+            }
+            other => other,
+        }))
+    };
+
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        ratelimit::acquire(&config, estimated).await;
+        let err = match provider.ask_stream(config.clone(), request).await {
+            Ok(mut stream) => match stream.next().await {
+                Some(Err(e)) => e,
+                first => return Ok(stamped(Box::pin(futures_util::stream::iter(first).chain(stream)))),
+            },
+            Err(e) => e,
+        };
+        ratelimit::observe_error(&config, &err);
+        match config.retry.as_ref().and_then(|policy| policy.delay(attempts, &err)) {
+            Some(delay) => tokio::time::sleep(delay).await,
+            None => return Err(err),
+        }
+    }
+}
+
+/// `provider.embed`, throttled per `config.rate_limit` (embeddings are not retried).
+pub(crate) async fn embed_throttled(
+    provider: &dyn Provider,
+    config: &AskConfig,
+    text: &str,
+    model: Option<&str>,
+) -> Result<Vec<f32>, AiError> {
+    ratelimit::acquire(config, ratelimit::estimate_text_tokens(text.len())).await;
+    provider
+        .embed(config, text, model)
+        .await
+        .inspect_err(|e| ratelimit::observe_error(config, e))
+}
+
+/// Record which backend and model answered (useful once fallbacks are involved), how long the
+/// call took since `started` (including rate-limit waits and retries) and what it cost.
+fn stamp(response: &mut AskResponse, config: &AskConfig, started: Instant) {
+    response.latency_ms = started.elapsed().as_millis();
+    response.provider = config.api.to_string();
+    // The provider may report a different model than asked (OpenRouter `models`, aliases)
+    if response.model.is_empty() {
+        response.model = config.model.clone();
+    }
+    let pricing = PricingTable::global();
+    response.cost = response.usage.as_ref().and_then(|usage| {
+        pricing
+            .cost(&config.api, &response.model, usage)
+            .or_else(|| pricing.cost(&config.api, &config.model, usage))
+    });
+}
+
+/// Time to the first generated chunk, and output speed from then until the stream completed.
+fn record_stream_timing(response: &mut AskResponse, started: Instant, first_token: Option<Instant>) {
+    let Some(first_token) = first_token else { return };
+    response.time_to_first_token_ms = Some(first_token.duration_since(started).as_millis());
+
+    let generating = first_token.elapsed().as_secs_f64();
+    let completion_tokens = response.usage.as_ref().and_then(|u| u.completion_tokens);
+    if let Some(tokens) = completion_tokens
+        && generating > 0.0
+    {
+        response.tokens_per_second = Some(tokens as f64 / generating);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorInfo;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(250),
+            jitter: 0.0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    fn overloaded(retry_after: Option<Duration>) -> AiError {
+        AiError::Overloaded(ErrorInfo { status: Some(529), retry_after, ..Default::default() })
+    }
+
+    #[test]
+    fn delay_doubles_up_to_max_delay() {
+        let policy = policy();
+        let err = overloaded(None);
+        assert_eq!(policy.delay(1, &err), Some(Duration::from_millis(100)));
+        assert_eq!(policy.delay(2, &err), Some(Duration::from_millis(200)));
+        assert_eq!(policy.delay(3, &err), Some(Duration::from_millis(250)));
+        assert_eq!(policy.delay(4, &err), None);
+    }
+
+    #[test]
+    fn delay_jitter_only_shortens() {
+        let policy = RetryPolicy { jitter: 0.5, ..policy() };
+        for _ in 0..100 {
+            let delay = policy.delay(2, &AiError::Timeout).unwrap();
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200), "{delay:?}");
+        }
+    }
+
+    #[test]
+    fn delay_follows_retry_after_within_max_delay() {
+        let policy = policy();
+        assert_eq!(policy.delay(1, &overloaded(Some(Duration::from_millis(40)))), Some(Duration::from_millis(40)));
+        assert_eq!(policy.delay(1, &overloaded(Some(Duration::from_secs(5)))), None);
+    }
+
+    #[test]
+    fn delay_skips_errors_outside_retry_on() {
+        let policy = RetryPolicy { retry_on: vec![RetryOn::RateLimited], ..policy() };
+        assert_eq!(policy.delay(1, &overloaded(None)), None);
+        assert_eq!(policy.delay(1, &AiError::Auth), None);
+        assert!(policy.delay(1, &AiError::RateLimited(ErrorInfo::default())).is_some());
+    }
+}
@@ -0,0 +1,129 @@
+//! Minimal Server-Sent Events reader over a `reqwest::Response`.
+
+use async_stream::try_stream;
+use futures_core::Stream;
+
+use crate::{error::AiError, util::map_reqwest_err};
+
+/// One SSE event: the optional `event:` name and its (joined) `data:` payload.
+#[derive(Clone, Debug, Default)]
+pub struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+}
+
+/// Splits the response body into events as chunks arrive. Comments and `id:`/`retry:` fields are ignored.
+pub fn sse_events(mut resp: reqwest::Response) -> impl Stream<Item = Result<SseEvent, AiError>> + Send {
+    try_stream! {
+        let mut buf: Vec<u8> = Vec::new();
+
+        while let Some(bytes) = resp.chunk().await.map_err(map_reqwest_err)? {
+            buf.extend_from_slice(&bytes);
+            for ev in take_events(&mut buf)? {
+                yield ev;
+            }
+        }
+
+        // Trailing event without a blank line after it
+        if let Some(ev) = parse_event(&buf)? {
+            yield ev;
+        }
+    }
+}
+
+/// Remove every complete event from the front of `buf`, leaving a partial one in place.
+fn take_events(buf: &mut Vec<u8>) -> Result<Vec<SseEvent>, AiError> {
+    let mut events = Vec::new();
+    while let Some((end, sep_len)) = find_event_end(buf) {
+        let raw: Vec<u8> = buf.drain(..end + sep_len).take(end).collect();
+        events.extend(parse_event(&raw)?);
+    }
+    Ok(events)
+}
+
+/// Position of the first blank line (event boundary) and the separator length.
+fn find_event_end(buf: &[u8]) -> Option<(usize, usize)> {
+    let lf = buf.windows(2).position(|w| w == b"\n\n").map(|i| (i, 2));
+    let crlf = buf.windows(4).position(|w| w == b"\r\n\r\n").map(|i| (i, 4));
+    match (lf, crlf) {
+        (Some(a), Some(b)) => Some(if a.0 <= b.0 { a } else { b }),
+        (a, b) => a.or(b),
+    }
+}
+
+fn parse_event(raw: &[u8]) -> Result<Option<SseEvent>, AiError> {
+    let text = std::str::from_utf8(raw).map_err(|e| AiError::Json(e.to_string()))?;
+    let mut ev = SseEvent::default();
+    let mut has_data = false;
+
+    for line in text.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() || line.starts_with(':') {
+            continue;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => ev.event = Some(value.to_string()),
+            "data" => {
+                if has_data {
+                    ev.data.push('\n');
+                }
+                ev.data.push_str(value);
+                has_data = true;
+            }
+            _ => {}
+        }
+    }
+
+    Ok((has_data || ev.event.is_some()).then_some(ev))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `body` in `chunk_len`-byte pieces, as the network might deliver it.
+    fn split(body: &str, chunk_len: usize) -> Vec<SseEvent> {
+        let mut buf = Vec::new();
+        let mut events = Vec::new();
+        for chunk in body.as_bytes().chunks(chunk_len) {
+            buf.extend_from_slice(chunk);
+            events.extend(take_events(&mut buf).unwrap());
+        }
+        events.extend(parse_event(&buf).unwrap());
+        events
+    }
+
+    #[test]
+    fn splits_events_across_chunk_boundaries() {
+        let body = "event: message_start\ndata: {\"a\":1}\n\n: keep-alive\n\ndata: [DONE]\n\n";
+        for chunk_len in [1, 3, 7, body.len()] {
+            let events = split(body, chunk_len);
+            assert_eq!(events.len(), 2, "chunk_len {chunk_len}");
+            assert_eq!(events[0].event.as_deref(), Some("message_start"));
+            assert_eq!(events[0].data, "{\"a\":1}");
+            assert_eq!(events[1].event, None);
+            assert_eq!(events[1].data, "[DONE]");
+        }
+    }
+
+    #[test]
+    fn handles_crlf_multiline_data_and_a_trailing_event() {
+        let events = split("data: one\r\ndata: two\r\n\r\nid: 7\nretry: 10\ndata:three", 5);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].data, "one\ntwo");
+        assert_eq!(events[1].data, "three");
+    }
+
+    #[test]
+    fn invalid_utf8_is_a_decode_error() {
+        let mut buf = b"data: \xff\n\n".to_vec();
+        assert!(matches!(take_events(&mut buf), Err(AiError::Json(_))));
+    }
+
+    #[test]
+    fn skips_comment_only_blocks() {
+        assert!(split(": ping\n\n: ping\n\n", 4).is_empty());
+    }
+}
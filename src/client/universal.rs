@@ -0,0 +1,272 @@
+//! Long-lived client: default config, per-provider connection pools and shared middleware.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_stream::try_stream;
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::ask::config::AskConfig;
+use crate::ask::msg::Msg;
+use crate::ask::request::{AskChunk, AskRequest};
+use crate::ask::response::AskResponse;
+use crate::ask::schema::JsonSchemaSpec;
+use crate::client::fallback::FallbackChain;
+use crate::client::provider::{AskStream, Provider, ProviderRegistry};
+use crate::client::retry::{ask_stream_with_retry, ask_with_retry, embed_throttled};
+use crate::error::AiError;
+use crate::util::{build_http_client, parse_json};
+
+/// Extra attempts `ask_json` makes when the output does not parse.
+pub const MAX_JSON_REPAIRS: usize = 2;
+
+/// Hooks run around every call made through a `Client` (logging, auth injection, redaction, ...).
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Before the call; may rewrite the config/request or reject the call.
+    async fn on_request(&self, _config: &mut AskConfig, _request: &mut AskRequest) -> Result<(), AiError> {
+        Ok(())
+    }
+
+    /// After a successful call (the final `Complete` chunk when streaming).
+    async fn on_response(&self, _request: &AskRequest, _response: &mut AskResponse) {}
+
+    async fn on_error(&self, _request: &AskRequest, _error: &AiError) {}
+}
+
+/// Settings baked into a pooled `reqwest::Client`; configs that agree on these share a pool.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct PoolKey {
+    api: String,
+    request_timeout: Duration,
+}
+
+impl PoolKey {
+    fn new(config: &AskConfig) -> Self {
+        Self {
+            api: config.api.to_string(),
+            request_timeout: config.request_timeout,
+        }
+    }
+}
+
+/// Reusable client. Cheap to clone; clones share connection pools and middleware.
+///
+/// `AskRequest.model` overrides the default config's model when set.
+#[derive(Clone)]
+pub struct Client {
+    chain: FallbackChain, // the default config, then its fallbacks
+    registry: ProviderRegistry,
+    middleware: Vec<Arc<dyn Middleware>>,
+    pools: Arc<Mutex<HashMap<PoolKey, reqwest::Client>>>,
+}
+
+impl Client {
+    pub fn new(config: AskConfig) -> Self {
+        Self {
+            chain: FallbackChain::new(vec![config]),
+            registry: ProviderRegistry::global().clone(),
+            middleware: Vec::new(),
+            pools: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Middleware runs in the order it was added.
+    pub fn with_middleware(mut self, middleware: Arc<dyn Middleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Tried in order by `ask` / `ask_stream` when the default config fails with a retryable
+    /// error. Fallbacks always use their own provider and model, ignoring `AskRequest.provider`
+    /// and `AskRequest.model`.
+    pub fn with_fallback(mut self, config: AskConfig) -> Self {
+        self.chain = self.chain.with_fallback(config);
+        self
+    }
+
+    /// Resolve custom providers against `registry` instead of the global one.
+    pub fn with_registry(mut self, registry: ProviderRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    pub fn config(&self) -> &AskConfig {
+        &self.chain.configs[0]
+    }
+
+    pub async fn ask(&self, request: AskRequest) -> Result<AskResponse, AiError> {
+        let started = Instant::now();
+        self.chain
+            .try_each(|index, config| self.ask_entry(config, for_entry(index, &request), started))
+            .await
+    }
+
+    /// `ask` against another config (e.g. a different provider), still using this client's pools.
+    pub async fn ask_with(&self, config: AskConfig, request: AskRequest) -> Result<AskResponse, AiError> {
+        self.ask_entry(config, request, Instant::now()).await
+    }
+
+    async fn ask_entry(&self, config: AskConfig, request: AskRequest, started: Instant) -> Result<AskResponse, AiError> {
+        let (provider, config, request) = self.prepare(config, request).await?;
+        match ask_with_retry(&*provider, config, &request, started).await {
+            Ok(mut response) => {
+                for m in &self.middleware {
+                    m.on_response(&request, &mut response).await;
+                }
+                Ok(response)
+            }
+            Err(e) => {
+                for m in &self.middleware {
+                    m.on_error(&request, &e).await;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    pub async fn ask_stream(&self, request: AskRequest) -> Result<AskStream<'static>, AiError> {
+        let started = Instant::now();
+        self.chain
+            .try_each(|index, config| self.ask_stream_entry(config, for_entry(index, &request), started))
+            .await
+    }
+
+    /// Resolves once the provider has started answering; later failures arrive as stream items.
+    pub async fn ask_stream_with(&self, config: AskConfig, request: AskRequest) -> Result<AskStream<'static>, AiError> {
+        self.ask_stream_entry(config, request, Instant::now()).await
+    }
+
+    async fn ask_stream_entry(
+        &self,
+        config: AskConfig,
+        request: AskRequest,
+        started: Instant,
+    ) -> Result<AskStream<'static>, AiError> {
+        let (provider, config, request) = self.prepare(config, request).await?;
+        let middleware = self.middleware.clone();
+
+        let mut stream: AskStream<'static> = Box::pin(try_stream! {
+            let mut inner = match ask_stream_with_retry(&*provider, config, &request, started).await {
+                Ok(inner) => inner,
+                Err(e) => {
+                    for m in &middleware {
+                        m.on_error(&request, &e).await;
+                    }
+                    Err(e)?
+                }
+            };
+
+            while let Some(chunk) = inner.next().await {
+                let mut chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        for m in &middleware {
+                            m.on_error(&request, &e).await;
+                        }
+                        Err(e)?
+                    }
+                };
+                if let AskChunk::Complete(response) = &mut chunk {
+                    for m in &middleware {
+                        m.on_response(&request, response).await;
+                    }
+                }
+                yield chunk;
+            }
+        });
+
+        // Report failures to open the stream from here rather than as its first item
+        match stream.next().await {
+            Some(Err(e)) => Err(e),
+            first => Ok(Box::pin(futures_util::stream::iter(first).chain(stream))),
+        }
+    }
+
+    /// Ask for JSON matching `T`'s schema and deserialize it. Providers enforce the schema where
+    /// they can; otherwise the parse error is fed back to the model for up to `MAX_JSON_REPAIRS`
+    /// more attempts.
+    pub async fn ask_json<T: DeserializeOwned + JsonSchema>(&self, mut request: AskRequest) -> Result<T, AiError> {
+        request.options.json_schema.get_or_insert_with(JsonSchemaSpec::of::<T>);
+        request.options.json_mode = Some(true);
+
+        let mut response = self.ask(request.clone()).await?;
+        for _ in 0..MAX_JSON_REPAIRS {
+            let err = match parse_json::<T>(&response.text) {
+                Ok(v) => return Ok(v),
+                Err(err) => err,
+            };
+
+            request.messages.push(Msg::assistant(response.text));
+            request.messages.push(Msg::user(format!(
+                "Your previous output could not be parsed ({err}). Re-emit only valid JSON matching the schema. No prose."
+            )));
+            response = self.ask(request.clone()).await?;
+        }
+        parse_json::<T>(&response.text).map_err(AiError::Json)
+    }
+
+    pub async fn embed(&self, text: &str, model: Option<&str>) -> Result<Vec<f32>, AiError> {
+        let config = self.pooled(self.config().clone())?;
+        let provider = self.registry.resolve(None, &config.api)?;
+        embed_throttled(&*provider, &config, text, model).await
+    }
+
+    pub async fn get_models(&self) -> Result<Value, AiError> {
+        let config = self.pooled(self.config().clone())?;
+        let provider = self.registry.resolve(None, &config.api)?;
+        provider.list_models(&config).await
+    }
+
+    async fn prepare(
+        &self,
+        config: AskConfig,
+        mut request: AskRequest,
+    ) -> Result<(Arc<dyn Provider>, AskConfig, AskRequest), AiError> {
+        let mut config = config.for_request(&request);
+        for m in &self.middleware {
+            m.on_request(&mut config, &mut request).await?;
+        }
+
+        let config = self.pooled(config)?;
+        let provider = self.registry.resolve(Some(&request.provider), &config.api)?;
+        Ok((provider, config, request))
+    }
+
+    /// Attach the shared HTTP client for this config's provider, building it on first use.
+    fn pooled(&self, mut config: AskConfig) -> Result<AskConfig, AiError> {
+        if config.http_client.is_some() {
+            return Ok(config);
+        }
+
+        let mut pools = self.pools.lock().unwrap_or_else(|e| e.into_inner());
+        let http = match pools.get(&PoolKey::new(&config)) {
+            Some(http) => http.clone(),
+            None => {
+                let http = build_http_client(&config)?;
+                pools.insert(PoolKey::new(&config), http.clone());
+                http
+            }
+        };
+        config.http_client = Some(http);
+        Ok(config)
+    }
+}
+
+/// The request as sent to entry `index` of the chain; fallbacks answer with their own provider
+/// and model.
+fn for_entry(index: usize, request: &AskRequest) -> AskRequest {
+    if index == 0 {
+        return request.clone();
+    }
+    AskRequest {
+        model: String::new(),
+        provider: String::new(),
+        ..request.clone()
+    }
+}
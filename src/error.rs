@@ -1,11 +1,23 @@
+use std::time::Duration;
+
 #[derive(thiserror::Error, Debug)]
 pub enum AiError {
     #[error("auth failed")]
     Auth,
-    #[error("rate limited")]
-    RateLimited,
+    #[error("rate limited: {0}")]
+    RateLimited(ErrorInfo),
     #[error("timeout")]
     Timeout,
+    #[error("bad request: {0}")]
+    BadRequest(ErrorInfo),
+    #[error("context length exceeded: {0}")]
+    ContextLengthExceeded(ErrorInfo),
+    #[error("content filtered: {0}")]
+    ContentFiltered(ErrorInfo),
+    #[error("provider overloaded: {0}")]
+    Overloaded(ErrorInfo),
+    #[error("server error: {0}")]
+    ServerError(ErrorInfo),
     #[error("provider error: {0}")]
     Provider(String),
     #[error("json error: {0}")]
@@ -17,3 +29,152 @@ pub enum AiError {
     #[error("unsupported provider")]
     Unsupported,
 }
+
+/// What the provider told us about a failed call.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorInfo {
+    pub status: Option<u16>,           // HTTP status (absent for errors reported mid-stream)
+    pub error_type: Option<String>,    // provider error type/code, e.g. "overloaded_error"
+    pub message: String,
+    pub request_id: Option<String>,    // quote this in support tickets
+    pub retry_after: Option<Duration>, // from `retry-after` / `retry-after-ms`
+}
+
+impl std::fmt::Display for ErrorInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(status) = self.status {
+            write!(f, "{status} ")?;
+        }
+        if let Some(error_type) = &self.error_type {
+            write!(f, "{error_type}: ")?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(request_id) = &self.request_id {
+            write!(f, " (request id {request_id})")?;
+        }
+        Ok(())
+    }
+}
+
+impl ErrorInfo {
+    /// Classify a provider error by HTTP status, then by error type and message.
+    pub fn into_error(self) -> AiError {
+        let error_type = self.error_type.as_deref().unwrap_or_default().to_lowercase();
+        let message = self.message.to_lowercase();
+        let context_length = error_type.contains("context_length")
+            || message.contains("context length")
+            || message.contains("context window")
+            || message.contains("prompt is too long")
+            || message.contains("too many tokens");
+        let content_filter = error_type.contains("content_filter")
+            || error_type.contains("content_policy")
+            || error_type.contains("safety");
+
+        match self.status {
+            Some(401 | 403) => AiError::Auth,
+            Some(408) => AiError::Timeout,
+            Some(429) => AiError::RateLimited(self),
+            Some(503 | 529) => AiError::Overloaded(self),
+            Some(500..=599) if error_type.contains("overloaded") => AiError::Overloaded(self),
+            Some(500..=599) => AiError::ServerError(self),
+            Some(400..=499) if context_length => AiError::ContextLengthExceeded(self),
+            Some(400..=499) if content_filter => AiError::ContentFiltered(self),
+            Some(400..=499) => AiError::BadRequest(self),
+            // No status (stream errors, SDK errors): go by type alone
+            _ if error_type.contains("auth") || error_type.contains("permission") || error_type.contains("invalid_api_key") => {
+                AiError::Auth
+            }
+            _ if error_type.contains("rate_limit") || error_type.contains("resource_exhausted") => AiError::RateLimited(self),
+            _ if error_type.contains("overloaded") || error_type.contains("unavailable") => AiError::Overloaded(self),
+            _ if context_length => AiError::ContextLengthExceeded(self),
+            _ if content_filter => AiError::ContentFiltered(self),
+            _ if error_type.contains("invalid_request") || error_type.contains("invalid_argument") => AiError::BadRequest(self),
+            _ if error_type.contains("server_error") || error_type.contains("api_error") || error_type.contains("internal") => {
+                AiError::ServerError(self)
+            }
+            _ => AiError::Provider(self.to_string()),
+        }
+    }
+}
+
+impl AiError {
+    /// Worth retrying unchanged: rate limits, overload, 5xx, timeouts and connection failures.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            AiError::RateLimited(_) | AiError::Overloaded(_) | AiError::ServerError(_) | AiError::Timeout | AiError::Http(_)
+        )
+    }
+
+    pub fn info(&self) -> Option<&ErrorInfo> {
+        match self {
+            AiError::RateLimited(info)
+            | AiError::BadRequest(info)
+            | AiError::ContextLengthExceeded(info)
+            | AiError::ContentFiltered(info)
+            | AiError::Overloaded(info)
+            | AiError::ServerError(info) => Some(info),
+            _ => None,
+        }
+    }
+
+    /// How long the provider asked us to wait, if it said.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.info().and_then(|info| info.retry_after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(status: Option<u16>, error_type: Option<&str>, message: &str) -> ErrorInfo {
+        ErrorInfo {
+            status,
+            error_type: error_type.map(str::to_string),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn into_error_classifies_by_status() {
+        assert!(matches!(info(Some(401), None, "").into_error(), AiError::Auth));
+        assert!(matches!(info(Some(408), None, "").into_error(), AiError::Timeout));
+        assert!(matches!(info(Some(429), None, "").into_error(), AiError::RateLimited(_)));
+        assert!(matches!(info(Some(529), None, "").into_error(), AiError::Overloaded(_)));
+        assert!(matches!(info(Some(500), Some("overloaded_error"), "").into_error(), AiError::Overloaded(_)));
+        assert!(matches!(info(Some(502), None, "").into_error(), AiError::ServerError(_)));
+        assert!(matches!(info(Some(400), None, "bad").into_error(), AiError::BadRequest(_)));
+    }
+
+    #[test]
+    fn into_error_refines_400s_by_type_and_message() {
+        let err = info(Some(400), Some("invalid_request_error"), "prompt is too long: 210000 tokens").into_error();
+        assert!(matches!(err, AiError::ContextLengthExceeded(_)));
+        let err = info(Some(400), Some("context_length_exceeded"), "").into_error();
+        assert!(matches!(err, AiError::ContextLengthExceeded(_)));
+        let err = info(Some(400), Some("content_policy_violation"), "").into_error();
+        assert!(matches!(err, AiError::ContentFiltered(_)));
+    }
+
+    #[test]
+    fn into_error_falls_back_to_type_without_status() {
+        assert!(matches!(info(None, Some("authentication_error"), "").into_error(), AiError::Auth));
+        assert!(matches!(info(None, Some("rate_limit_error"), "").into_error(), AiError::RateLimited(_)));
+        assert!(matches!(info(None, Some("overloaded_error"), "").into_error(), AiError::Overloaded(_)));
+        assert!(matches!(info(None, Some("api_error"), "").into_error(), AiError::ServerError(_)));
+        assert!(matches!(info(None, Some("something_new"), "").into_error(), AiError::Provider(_)));
+    }
+
+    #[test]
+    fn into_error_keeps_retry_after() {
+        let err = ErrorInfo {
+            retry_after: Some(Duration::from_secs(3)),
+            ..info(Some(429), None, "slow down")
+        }
+        .into_error();
+        assert!(err.is_retryable());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(3)));
+    }
+}
@@ -1,13 +1,14 @@
-use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
 
-use futures_core::Stream;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
 
 use crate::ask::config::AskConfig;
-use crate::ask::request::{AskChunk, AskRequest};
-use crate::client::anthropic::AnthropicApi;
-use crate::client::openai::OpenAiApi;
-use crate::client::ProviderAPI;
+use crate::ask::request::AskRequest;
+use crate::client::provider::{AskStream, Provider, ProviderRegistry};
+use crate::client::retry::{ask_stream_with_retry, ask_with_retry, embed_throttled};
 use crate::error::AiError;
 
 pub mod error;
@@ -16,57 +17,49 @@ pub mod ask;
 // pub mod types;
 pub mod util;
 
+pub use crate::client::universal::{Client, Middleware};
+pub use schemars;
+
+/// One-shot calls. `AskRequest.model`, when set, overrides `AskConfig.model` (as on `Client`).
 pub struct CnctdAi;
 
 impl CnctdAi {
     pub async fn ask(ask_request: AskRequest, ask_config: AskConfig) -> Result<Value, AiError> {
-        let ask_response = match ask_config.api {
-            ProviderAPI::OpenAI => OpenAiApi::ask(ask_config, &ask_request).await?,
-            ProviderAPI::Anthropic => AnthropicApi::ask(ask_config, &ask_request).await?,
-        };
+        let provider = Self::resolve(Some(&ask_request.provider), &ask_config)?;
+        let ask_response = ask_with_retry(&*provider, ask_config.for_request(&ask_request), &ask_request, Instant::now()).await?;
 
         Ok(json!(ask_response) )
     }
 
-    pub async fn ask_stream<'a>(ask_request: &'a AskRequest, ask_config: AskConfig) -> Result<Pin<Box<dyn Stream<Item = Result<AskChunk, AiError>> + Send + 'a>>, AiError> {
-        match ask_config.api {
-            ProviderAPI::OpenAI => {
-                let s = OpenAiApi::ask_stream(ask_config, ask_request).await?;
-                let s: Pin<Box<dyn Stream<Item = Result<AskChunk, AiError>> + Send + 'a>> = Box::pin(s);
-                Ok(s)
-            }
-            ProviderAPI::Anthropic => {
-                let s = AnthropicApi::ask_stream(ask_config, ask_request).await?;
-                let s: Pin<Box<dyn Stream<Item = Result<AskChunk, AiError>> + Send + 'a>> = Box::pin(s);
-                Ok(s)
-            }
-        }
+    /// Structured output deserialized into `T`; see `Client::ask_json`.
+    pub async fn ask_json<T: DeserializeOwned + JsonSchema>(ask_request: AskRequest, ask_config: AskConfig) -> Result<T, AiError> {
+        Client::new(ask_config).ask_json(ask_request).await
+    }
+
+    pub async fn ask_stream<'a>(ask_request: &'a AskRequest, ask_config: AskConfig) -> Result<AskStream<'a>, AiError> {
+        let provider = Self::resolve(Some(&ask_request.provider), &ask_config)?;
+        ask_stream_with_retry(&*provider, ask_config.for_request(ask_request), ask_request, Instant::now()).await
     }
 
     pub async fn get_models(ask_config: &AskConfig) -> Result<Value, AiError> {
-        let models = match ask_config.api {
-            ProviderAPI::OpenAI => OpenAiApi::get_models(ask_config).await?,
-            ProviderAPI::Anthropic => AnthropicApi::get_models(ask_config).await?,
-        };
+        let provider = Self::resolve(None, ask_config)?;
+        let models = provider.list_models(ask_config).await?;
 
         Ok(models)
     }
-}
-
 
-// fn openrouter_client() -> Result<Client<OpenAIConfig>, Box<dyn std::error::Error>> {
-//     let key = std::env::var("OPENROUTER_API_KEY")?;
-
-//     // Optional but recommended attribution headers (helps with rate limits/analytics on their end)
-//     let mut headers = HeaderMap::new();
-//     headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", key))?);
-//     headers.insert("Content-Type", HeaderValue::from_static("application/json"));
+    pub async fn get_embedding(text: &str, ask_config: &AskConfig, model: Option<&str>) -> Result<Vec<f32>, AiError> {
+        let provider = Self::resolve(None, ask_config)?;
+        embed_throttled(&*provider, ask_config, text, model).await
+    }
 
-//     let http = reqwest::Client::builder()
-//         .user_agent("cnctd-ai/0.1")
-//         .default_headers(headers)
-//         .build()?;
+    /// Make a custom provider available under `id` (matched against `AskRequest.provider`
+    /// and `ProviderAPI::Custom`).
+    pub fn register_provider(id: impl Into<String>, provider: Arc<dyn Provider>) {
+        ProviderRegistry::global().register(id, provider);
+    }
 
-//     let cfg = OpenAIConfig::new().with_api_base("https://openrouter.ai/api/v1");
-//     Ok(Client::with_config(cfg).with_http_client(http))
-// }
\ No newline at end of file
+    fn resolve(request_provider: Option<&str>, ask_config: &AskConfig) -> Result<Arc<dyn Provider>, AiError> {
+        ProviderRegistry::global().resolve(request_provider, &ask_config.api)
+    }
+}
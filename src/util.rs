@@ -1,17 +1,21 @@
-use core::error;
+use std::time::Duration;
 
 use serde::de::DeserializeOwned;
+use serde_json::Value;
 
-use crate::{ask::config::AskConfig, error::AiError};
+use crate::{
+    ask::config::AskConfig,
+    error::{AiError, ErrorInfo},
+};
 
 pub fn parse_json<T: DeserializeOwned>(s: &str) -> Result<T, String> {
     if let Ok(v) = serde_json::from_str::<T>(s) {
         return Ok(v);
     }
-    if let (Some(start), Some(end)) = (s.find('{'), s.rfind('}')) {
-        if let Ok(v) = serde_json::from_str::<T>(&s[start..=end]) {
-            return Ok(v);
-        }
+    if let (Some(start), Some(end)) = (s.find('{'), s.rfind('}'))
+        && let Ok(v) = serde_json::from_str::<T>(&s[start..=end])
+    {
+        return Ok(v);
     }
     let cleaned = s
         .trim()
@@ -22,12 +26,77 @@ pub fn parse_json<T: DeserializeOwned>(s: &str) -> Result<T, String> {
     serde_json::from_str::<T>(cleaned).map_err(|e| e.to_string())
 }
 
+/// Parse streamed/returned tool arguments; keeps the raw string if the model emitted invalid JSON.
+pub fn parse_tool_args(s: &str) -> Value {
+    if s.trim().is_empty() {
+        return Value::Object(Default::default());
+    }
+    serde_json::from_str(s).unwrap_or_else(|_| Value::String(s.to_string()))
+}
+
+/// HTTP client for one call: the config's shared `http_client` if set, otherwise a fresh one.
 pub fn get_http_client(ask_config: &AskConfig) -> Result<reqwest::Client, AiError> {
-    Ok(
-        reqwest::Client::builder()
-            .user_agent(format!("cnctd-ai-{}-api", ask_config.api.to_string().to_lowercase()))
-            .timeout(ask_config.request_timeout)
-            .build()
-            .map_err(|e| AiError::Http(e.to_string()))?
-    )
+    match &ask_config.http_client {
+        Some(http) => Ok(http.clone()),
+        None => build_http_client(ask_config),
+    }
+}
+
+/// HTTP client for one provider. `request_timeout` bounds connecting and every read, so a hung
+/// request fails while a long-running stream that keeps producing bytes is not cut off.
+pub fn build_http_client(ask_config: &AskConfig) -> Result<reqwest::Client, AiError> {
+    reqwest::Client::builder()
+        .user_agent(format!("cnctd-ai-{}-api", ask_config.api.to_string().to_lowercase()))
+        .connect_timeout(ask_config.request_timeout)
+        .read_timeout(ask_config.request_timeout)
+        .build()
+        .map_err(map_reqwest_err)
+}
+
+/// A message part `backend` cannot take. Failing here beats sending the turn without it and
+/// getting a confusing answer (or a 400) back.
+pub fn unsupported_part(backend: &str, part: &str) -> AiError {
+    AiError::BadRequest(ErrorInfo {
+        message: format!("{backend} does not accept {part} input"),
+        ..Default::default()
+    })
+}
+
+/// Join the configured base url and an endpoint path, tolerating slashes on either side.
+pub fn endpoint(ask_config: &AskConfig, path: &str) -> String {
+    format!("{}/{}", ask_config.url.trim_end_matches('/'), path.trim_start_matches('/'))
+}
+
+/// Status, request id and retry hint of a failed response; adapters add type and message from the body.
+pub fn error_info(resp: &reqwest::Response) -> ErrorInfo {
+    let header = |name: &str| resp.headers().get(name).and_then(|v| v.to_str().ok());
+    // `retry-after-ms` (OpenAI, Azure) is finer-grained than `retry-after` seconds; HTTP dates are ignored
+    let retry_after = header("retry-after-ms")
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .and_then(|ms| Duration::try_from_secs_f64(ms / 1000.0).ok())
+        .or_else(|| {
+            header("retry-after")
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .and_then(|s| Duration::try_from_secs_f64(s).ok())
+        });
+
+    ErrorInfo {
+        status: Some(resp.status().as_u16()),
+        request_id: header("request-id").or_else(|| header("x-request-id")).map(str::to_string),
+        retry_after,
+        ..Default::default()
+    }
+}
+
+pub fn map_reqwest_err(e: reqwest::Error) -> AiError {
+    if e.is_timeout() {
+        AiError::Timeout
+    } else if e.is_builder() {
+        // Bad url or header in the config; sending it again will not help
+        AiError::Provider(e.to_string())
+    } else if e.is_decode() {
+        AiError::Json(e.to_string())
+    } else {
+        AiError::Http(e.to_string())
+    }
 }
\ No newline at end of file
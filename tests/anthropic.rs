@@ -0,0 +1,194 @@
+//! `AnthropicApi` against a local stand-in for the Messages API.
+
+use cnctd_ai::ask::config::AskConfig;
+use cnctd_ai::ask::msg::Msg;
+use cnctd_ai::ask::request::{AskChunk, AskOptions, AskRequest};
+use cnctd_ai::client::anthropic::AnthropicApi;
+use cnctd_ai::client::ProviderAPI;
+use cnctd_ai::error::AiError;
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// A request as the server saw it.
+struct Received {
+    head: String,
+    body: Value,
+}
+
+/// Serve one connection with `status`, `content_type` and `body`; the handle yields the request.
+async fn serve_once(status: &str, content_type: &str, body: String) -> (String, JoinHandle<Received>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/v1", listener.local_addr().unwrap());
+    let response = format!(
+        "HTTP/1.1 {status}\r\ncontent-type: {content_type}\r\ncontent-length: {}\r\nrequest-id: req_test\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    );
+
+    let handle = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let (head, body_start) = loop {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+            if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break (String::from_utf8_lossy(&buf[..end]).to_lowercase(), end + 4);
+            }
+        };
+        let length = head
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .map(|n| n.trim().parse::<usize>().unwrap())
+            .unwrap_or(0);
+        while buf.len() < body_start + length {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        socket.write_all(response.as_bytes()).await.unwrap();
+        socket.shutdown().await.unwrap();
+        Received {
+            head,
+            body: serde_json::from_slice(&buf[body_start..body_start + length]).unwrap(),
+        }
+    });
+    (url, handle)
+}
+
+fn config(url: String) -> AskConfig {
+    AskConfig::new("claude-sonnet-4-5".to_string(), ProviderAPI::Anthropic, "test-key".to_string(), Some(url), None)
+}
+
+fn request() -> AskRequest {
+    AskRequest {
+        system: Some("Be brief.".to_string()),
+        system_cache: None,
+        messages: vec![Msg::user("Hi")],
+        options: AskOptions { max_output_tokens: Some(64), ..Default::default() },
+        context_refs: Vec::new(),
+        tools: Vec::new(),
+        tool_choice: None,
+        provider: String::new(),
+        model: String::new(),
+    }
+}
+
+fn sse(events: &[Value]) -> String {
+    events
+        .iter()
+        .map(|data| format!("event: {}\ndata: {data}\n\n", data["type"].as_str().unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn ask_sends_messages_body_and_parses_reply() {
+    let reply = json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "content": [{ "type": "text", "text": "Hello!" }],
+        "stop_reason": "end_turn",
+        "usage": { "input_tokens": 10, "output_tokens": 3, "cache_read_input_tokens": 4 },
+    });
+    let (url, server) = serve_once("200 OK", "application/json", reply.to_string()).await;
+
+    let response = AnthropicApi::ask(config(url), &request()).await.unwrap();
+    assert_eq!(response.text, "Hello!");
+    assert_eq!(response.finish_reason, "stop");
+    assert_eq!(response.response_id.as_deref(), Some("msg_01"));
+    let usage = response.usage.unwrap();
+    assert_eq!(usage.prompt_tokens, Some(14));
+    assert_eq!(usage.cached_tokens, Some(4));
+    assert_eq!(usage.completion_tokens, Some(3));
+
+    let received = server.await.unwrap();
+    assert!(received.head.starts_with("post /v1/messages "));
+    assert!(received.head.contains("x-api-key: test-key"));
+    assert!(received.head.contains("anthropic-version: 2023-06-01"));
+    assert_eq!(received.body["model"], "claude-sonnet-4-5");
+    assert_eq!(received.body["max_tokens"], 64);
+    assert_eq!(received.body["system"], "Be brief.");
+    assert_eq!(received.body["stream"], false);
+    assert_eq!(received.body["messages"][0]["role"], "user");
+}
+
+#[tokio::test]
+async fn ask_maps_error_status() {
+    let error = json!({ "type": "error", "error": { "type": "overloaded_error", "message": "Overloaded" } });
+    let (url, _server) = serve_once("529 Overloaded", "application/json", error.to_string()).await;
+
+    match AnthropicApi::ask(config(url), &request()).await {
+        Err(AiError::Overloaded(info)) => {
+            assert_eq!(info.status, Some(529));
+            assert_eq!(info.request_id.as_deref(), Some("req_test"));
+            assert_eq!(info.message, "Overloaded");
+        }
+        other => panic!("expected Overloaded, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn ask_stream_yields_deltas_then_complete() {
+    let events = sse(&[
+        json!({ "type": "message_start", "message": {
+            "id": "msg_02", "type": "message", "role": "assistant", "content": [],
+            "usage": { "input_tokens": 8, "output_tokens": 1 },
+        }}),
+        json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "text", "text": "" } }),
+        json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": "Hel" } }),
+        json!({ "type": "ping" }),
+        json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": "lo" } }),
+        json!({ "type": "content_block_stop", "index": 0 }),
+        json!({ "type": "message_delta", "delta": { "stop_reason": "max_tokens" }, "usage": { "output_tokens": 2 } }),
+        json!({ "type": "message_stop" }),
+    ]);
+    let (url, server) = serve_once("200 OK", "text/event-stream", events).await;
+
+    let request = request();
+    let stream = AnthropicApi::ask_stream(config(url), &request).await.unwrap();
+    let chunks: Vec<AskChunk> = stream.map(Result::unwrap).collect().await;
+
+    let deltas: Vec<&str> = chunks
+        .iter()
+        .filter_map(|c| match c {
+            AskChunk::Delta { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(deltas, ["Hel", "lo"]);
+
+    let Some(AskChunk::Complete(response)) = chunks.last() else {
+        panic!("stream did not end with Complete");
+    };
+    assert_eq!(response.text, "Hello");
+    assert_eq!(response.finish_reason, "length");
+    assert_eq!(response.response_id.as_deref(), Some("msg_02"));
+    let usage = response.usage.as_ref().unwrap();
+    assert_eq!(usage.prompt_tokens, Some(8));
+    assert_eq!(usage.completion_tokens, Some(2));
+
+    assert_eq!(server.await.unwrap().body["stream"], true);
+}
+
+#[tokio::test]
+async fn ask_stream_fails_without_message_stop() {
+    let events = sse(&[
+        json!({ "type": "message_start", "message": {
+            "id": "msg_03", "type": "message", "role": "assistant", "content": [],
+            "usage": { "input_tokens": 8, "output_tokens": 1 },
+        }}),
+        json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "text", "text": "" } }),
+        json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": "Hel" } }),
+    ]);
+    let (url, _server) = serve_once("200 OK", "text/event-stream", events).await;
+
+    let request = request();
+    let stream = AnthropicApi::ask_stream(config(url), &request).await.unwrap();
+    let chunks: Vec<Result<AskChunk, AiError>> = stream.collect().await;
+
+    assert!(matches!(chunks.first(), Some(Ok(AskChunk::Delta { .. }))));
+    assert!(matches!(chunks.last(), Some(Err(AiError::Http(_)))));
+    assert!(!chunks.iter().any(|c| matches!(c, Ok(AskChunk::Complete(_)))));
+}